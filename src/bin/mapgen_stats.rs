@@ -0,0 +1,324 @@
+//! Runs the map builders headlessly and reports how the levels they build turn out, so that
+//! their configurations can be tuned with data rather than by eyeballing a handful of runs.
+//!
+//! ```text
+//! mapgen_stats [--runs N] [--seed S] [--depth D] [--final-depth F] [--ascii]
+//!              [--CONFIG FIELD=VALUE...] [BUILDER...]
+//! ```
+//!
+//! `CONFIG` is one of `bsp-dungeon`, `bsp-interior` or `cellular-automata`, and `FIELD` one of
+//! the fields of that configuration, like `--bsp-dungeon min_room_width=4`.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use bracket_lib::prelude::{DijkstraMap, RandomNumberGenerator};
+use legion::{systems::CommandBuffer, IntoQuery, World};
+use strum::IntoEnumIterator;
+
+use rktrl_lib::mapgen_api::{
+    builder_chain, BuilderKind, MapBuilder, MapgenSettings, Name, RexAssets, TileType,
+};
+
+const WIDTH: i32 = 80;
+const HEIGHT: i32 = 43;
+
+struct Options {
+    runs: u64,
+    seed: u64,
+    depth: i32,
+    ascii: bool,
+    settings: MapgenSettings,
+    kinds: Vec<BuilderKind>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            runs: 100,
+            seed: 0,
+            depth: 1,
+            ascii: false,
+            settings: MapgenSettings::default(),
+            kinds: vec![],
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--runs" => options.runs = Self::value(&arg, args.next())?,
+                "--seed" => options.seed = Self::value(&arg, args.next())?,
+                "--depth" => options.depth = Self::value(&arg, args.next())?,
+                "--final-depth" => options.settings.final_depth = Self::value(&arg, args.next())?,
+                "--ascii" => options.ascii = true,
+                "--bsp-dungeon" | "--bsp-interior" | "--cellular-automata" => {
+                    Self::configure(&mut options.settings, &arg, args.next())?
+                }
+                name => options.kinds.push(
+                    BuilderKind::iter()
+                        .find(|kind| kind.to_string().eq_ignore_ascii_case(name))
+                        .ok_or_else(|| format!("Unknown builder {}", name))?,
+                ),
+            }
+        }
+        if options.kinds.is_empty() {
+            options.kinds = BuilderKind::iter().collect();
+        }
+        Ok(options)
+    }
+
+    /// Sets one field of the configuration picked by `flag` from `FIELD=VALUE`
+    fn configure(
+        settings: &mut MapgenSettings,
+        flag: &str,
+        assignment: Option<String>,
+    ) -> Result<(), String> {
+        let assignment = assignment.ok_or_else(|| format!("{} needs FIELD=VALUE", flag))?;
+        let mut sides = assignment.splitn(2, '=');
+        let field = sides.next().unwrap_or_default();
+        let value = sides.next().map(str::to_string);
+
+        let bsp = match flag {
+            "--bsp-dungeon" => Some(&mut settings.bsp_dungeon),
+            "--bsp-interior" => Some(&mut settings.bsp_interior),
+            _ => None,
+        };
+        if let Some(bsp) = bsp {
+            match field {
+                "subdivision_variance" => bsp.subdivision_variance = Self::value(field, value)?,
+                "depth" => bsp.depth = Self::value(field, value)?,
+                "min_room_width" => bsp.min_room_width = Self::value(field, value)?,
+                "max_room_width" => bsp.max_room_width = Self::value(field, value)?,
+                "min_room_height" => bsp.min_room_height = Self::value(field, value)?,
+                "max_room_height" => bsp.max_room_height = Self::value(field, value)?,
+                "min_padding" => bsp.min_padding = Self::value(field, value)?,
+                "max_padding" => bsp.max_padding = Self::value(field, value)?,
+                _ => return Err(format!("Unknown field {} for {}", field, flag)),
+            }
+        } else {
+            let cellular_automata = &mut settings.cellular_automata;
+            match field {
+                "initial_wall_percent" => {
+                    cellular_automata.initial_wall_percent = Self::value(field, value)?
+                }
+                "open_area_steps" => cellular_automata.open_area_steps = Self::value(field, value)?,
+                "steps" => cellular_automata.steps = Self::value(field, value)?,
+                "min_floor_percent" => {
+                    cellular_automata.min_floor_percent = Self::value(field, value)?
+                }
+                _ => return Err(format!("Unknown field {} for {}", field, flag)),
+            }
+        }
+        Ok(())
+    }
+
+    fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+        value
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| format!("{} needs a numeric value", flag))
+    }
+}
+
+/// What one level turned out like
+struct Sample {
+    duration: Duration,
+    floor_percent: f32,
+    rooms: Option<usize>,
+    /// How much of the walkable area can be reached from the start
+    connected_percent: f32,
+    /// `None` if the stairs can't be reached
+    stairs_distance: Option<f32>,
+    rejected: bool,
+    spawns: BTreeMap<String, usize>,
+}
+
+fn sample(kind: BuilderKind, seed: u64, options: &Options, rex_assets: &RexAssets) -> Sample {
+    let mut rng = RandomNumberGenerator::seeded(seed);
+    let start_time = Instant::now();
+    let mut builder = builder_chain(
+        kind,
+        &options.settings,
+        &mut rng,
+        WIDTH,
+        HEIGHT,
+        options.depth,
+        rex_assets,
+    );
+    builder.build_map(&mut rng);
+    let duration = start_time.elapsed();
+
+    let map = builder.get_map();
+    let start = builder.get_starting_position();
+    let walkable: Vec<_> = map
+        .position_set()
+        .into_iter()
+        .filter(|position| map[position].is_walkable())
+        .collect();
+
+    let dijkstra_map = DijkstraMap::new(
+        map.width,
+        map.height,
+        &[map.pos_idx(start)],
+        &map,
+        std::f32::MAX,
+    );
+    let stairs_distance = walkable
+        .iter()
+        .filter(|position| map[*position] == TileType::DownStairs)
+        .map(|position| dijkstra_map.map[map.pos_idx(*position)])
+        .filter(|distance| *distance < std::f32::MAX)
+        .min_by(|a, b| a.partial_cmp(b).unwrap());
+    let reachable = walkable
+        .iter()
+        .filter(|position| dijkstra_map.map[map.pos_idx(**position)] < std::f32::MAX)
+        .count();
+
+    let mut world = World::default();
+    let mut commands = CommandBuffer::new(&world);
+    builder.spawn_entities(&mut commands, &mut rng);
+    commands.flush(&mut world);
+    let mut spawns = BTreeMap::new();
+    for name in <&Name>::query().iter(&world) {
+        *spawns.entry(name.to_string()).or_default() += 1;
+    }
+
+    if options.ascii {
+        println!("{} #{}", kind, seed);
+        let markers = vec![(start, '@')].into_iter().collect();
+        println!("{}", map.to_ascii_with_markers(&markers));
+    }
+
+    Sample {
+        duration,
+        floor_percent: walkable.len() as f32 * 100.0 / (map.width * map.height) as f32,
+        rooms: builder.build_data().rooms.as_ref().map(Vec::len),
+        connected_percent: reachable as f32 * 100.0 / walkable.len().max(1) as f32,
+        stairs_distance,
+        rejected: kind
+            .validator(options.depth, options.settings.final_depth)
+            .validate(&map, start)
+            .is_err(),
+        spawns,
+    }
+}
+
+fn report(kind: BuilderKind, samples: &[Sample]) {
+    let runs = samples.len() as f32;
+    let mean = |f: &dyn Fn(&Sample) -> f32| samples.iter().map(f).sum::<f32>() / runs;
+    let max_duration = samples.iter().map(|s| s.duration).max().unwrap_or_default();
+    let reachable: Vec<f32> = samples.iter().filter_map(|s| s.stairs_distance).collect();
+
+    println!("== {} ({} runs)", kind, samples.len());
+    println!(
+        "  time:        {:.2}ms mean, {:.2}ms max",
+        mean(&|s| s.duration.as_secs_f32() * 1000.0),
+        max_duration.as_secs_f32() * 1000.0
+    );
+    println!("  floor:       {:.1}%", mean(&|s| s.floor_percent));
+    if samples.iter().all(|s| s.rooms.is_some()) {
+        println!("  rooms:       {:.1}", mean(&|s| s.rooms.unwrap() as f32));
+    }
+    println!("  connected:   {:.1}%", mean(&|s| s.connected_percent));
+    if reachable.is_empty() {
+        println!("  stairs:      never reachable");
+    } else {
+        println!(
+            "  stairs:      {:.1} steps mean, {} unreachable",
+            reachable.iter().sum::<f32>() / reachable.len() as f32,
+            samples.len() - reachable.len()
+        );
+    }
+    println!(
+        "  rejected:    {}",
+        samples.iter().filter(|s| s.rejected).count()
+    );
+
+    let mut spawns: BTreeMap<&str, usize> = BTreeMap::new();
+    for sample in samples {
+        for (name, count) in &sample.spawns {
+            *spawns.entry(name).or_default() += count;
+        }
+    }
+    println!("  spawns per level:");
+    for (name, count) in spawns {
+        println!("    {:<24}{:.2}", name, count as f32 / runs);
+    }
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!(
+                "Usage: mapgen_stats [--runs N] [--seed S] [--depth D] [--final-depth F] \
+                 [--ascii] [--CONFIG FIELD=VALUE...] [BUILDER...]"
+            );
+            eprintln!("Configs: bsp-dungeon, bsp-interior, cellular-automata");
+            eprintln!(
+                "Builders: {}",
+                BuilderKind::iter()
+                    .map(|kind| kind.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let rex_assets = RexAssets::new();
+    for kind in &options.kinds {
+        let samples: Vec<Sample> = (options.seed..options.seed + options.runs)
+            .map(|seed| sample(*kind, seed, &options, &rex_assets))
+            .collect();
+        report(*kind, &samples);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = parse(&[
+            "--runs",
+            "5",
+            "maze",
+            "--ascii",
+            "Dla",
+            "--final-depth",
+            "4",
+        ])
+        .unwrap();
+        assert_eq!(options.runs, 5);
+        assert_eq!(options.settings.final_depth, 4);
+        assert!(options.ascii);
+        assert_eq!(options.kinds.len(), 2);
+
+        assert_eq!(parse(&[]).unwrap().kinds.len(), BuilderKind::iter().count());
+        assert!(parse(&["--runs"]).is_err());
+        assert!(parse(&["nonsense"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_configs() {
+        let options = parse(&[
+            "--bsp-dungeon",
+            "depth=4",
+            "--cellular-automata",
+            "min_floor_percent=25.5",
+        ])
+        .unwrap();
+        assert_eq!(options.settings.bsp_dungeon.depth, 4);
+        assert_eq!(options.settings.bsp_interior.depth, 5);
+        assert_eq!(options.settings.cellular_automata.min_floor_percent, 25.5);
+
+        assert!(parse(&["--bsp-interior"]).is_err());
+        assert!(parse(&["--bsp-interior", "depth"]).is_err());
+        assert!(parse(&["--bsp-interior", "depth=deep"]).is_err());
+        assert!(parse(&["--cellular-automata", "depth=4"]).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use bracket_lib::prelude::{FontCharType, RGB};
 use legion::Entity;
+use strum_macros::IntoStaticStr;
 
 use crate::systems::prelude::{Input, Position};
 
@@ -9,7 +10,7 @@ pub enum UseTarget {
     Position(Position),
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone, IntoStaticStr)]
 pub enum Label {
     Root,
     Turn {
@@ -35,6 +36,11 @@ pub enum Label {
         target_position: Position,
     },
     NextLevelIntent,
+    OpenDoorIntent {
+        position: Position,
+    },
+    CloseDoorIntent,
+    SearchIntent,
     MeleeIntent {
         target_position: Position,
     },
@@ -49,6 +55,14 @@ pub enum Label {
         item: Entity,
         target: UseTarget,
     },
+    BuyIntent {
+        vendor: Entity,
+        item: Entity,
+    },
+    SellIntent {
+        vendor: Entity,
+        item: Entity,
+    },
 
     // Actions (when some data translation is needed from the intent)
     MeleeAction {
@@ -68,6 +82,15 @@ pub enum Label {
     MovementBlocked,
     NoStairsHere,
     MovedToNextLevel,
+    DoorOpened {
+        door: Entity,
+    },
+    DoorClosed {
+        door: Entity,
+    },
+    NoDoorToClose,
+    NothingToExplore,
+    NoKnownStairs,
 
     // Effects - Combat
     Hit,
@@ -102,15 +125,29 @@ pub enum Label {
     Spotted {
         hidden: Entity,
     },
+    NothingFound,
+    CannotRest,
 
     // Effects - Pickup
     PickupNothingHere,
     PickupDone,
+    PickupBlocked,
 
     // Effects - Inventory management
     EquipDone,
     DropDone,
     RemoveDone,
+    CannotRemoveCursed,
+
+    // Effects - Trade
+    BuyDone {
+        price: i32,
+    },
+    SellDone {
+        price: i32,
+    },
+    CannotAfford,
+    PurchaseBlocked,
 
     // Effects - Hunger
     Ate {
@@ -126,4 +163,10 @@ pub enum Label {
     TooFarAway,
     NoValidTargets,
     MagicMapping,
+    ItemIdentified {
+        item: Entity,
+    },
+    CurseRemoved {
+        item: Entity,
+    },
 }
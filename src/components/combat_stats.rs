@@ -16,6 +16,11 @@ impl CombatStats {
         new.hp = new_hp;
         new
     }
+
+    /// How much weight can be carried without becoming encumbered
+    pub fn carry_capacity(&self) -> f32 {
+        10.0 + self.power as f32 * 4.0
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TypeUuid)]
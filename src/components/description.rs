@@ -0,0 +1,13 @@
+use macro_attr::*;
+use newtype_derive::*;
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+macro_attr! {
+    /// Catalog key of the flavor text shown when looking at an entity
+    #[derive(Clone, PartialEq, Eq, Hash,
+             Serialize, Deserialize, TypeUuid,
+             NewtypeDebug!, NewtypeDeref!, NewtypeFrom!, NewtypeDisplay!)]
+    #[uuid = "c2d1b7e4-5a38-4f0e-8e7d-3b9a61f4c2a5"]
+    pub struct Description(String);
+}
@@ -0,0 +1,26 @@
+use bracket_lib::prelude::{to_cp437, FontCharType};
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Closed doors block both movement and sight, see `Map::close_door`
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "57cacb8c-15db-4042-b1e3-f595e3809037"]
+pub struct Door {
+    pub open: bool,
+}
+
+impl Door {
+    #[must_use]
+    pub const fn new(open: bool) -> Self {
+        Door { open }
+    }
+
+    #[must_use]
+    pub fn glyph(&self) -> FontCharType {
+        if self.open {
+            to_cp437('/')
+        } else {
+            to_cp437('+')
+        }
+    }
+}
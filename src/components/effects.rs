@@ -49,3 +49,11 @@ impl Confusion {
         })
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "a349884b-f3c7-4bea-a1ca-a525ce9824c6"]
+pub struct Identifies;
+
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "6a5e8fab-b1bb-4cbf-9193-d4d4ef1f9e87"]
+pub struct RemovesCurse;
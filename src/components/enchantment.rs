@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Magical modifier on top of an item's `MeleePowerBonus` / `DefenseBonus`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "52398ddd-a785-4f72-8a5f-32a07b41f47d"]
+pub struct Enchantment {
+    pub level: i32,
+}
+
+impl Enchantment {
+    #[must_use]
+    pub fn new(level: i32) -> Self {
+        Enchantment { level }
+    }
+
+    pub fn level_of(maybe_enchantment: Option<&Enchantment>) -> i32 {
+        maybe_enchantment.map_or(0, |enchantment| enchantment.level)
+    }
+}
+
+/// Cursed items can't be removed once equipped
+#[derive(PartialEq, Default, Debug, Clone, TypeUuid, Serialize, Deserialize)]
+#[uuid = "cbd0d2e2-88ab-42bb-a68b-a4bc4bae8dd6"]
+pub struct Cursed;
@@ -1,8 +1,9 @@
 use legion::Entity;
 use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 use type_uuid::TypeUuid;
 
-#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug, EnumIter)]
 pub enum EquipmentSlot {
     Melee,
     Shield,
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Money carried by an entity. Items with a `Gold` component are piles lying around.
+#[derive(PartialEq, Copy, Clone, Debug, Default, Serialize, Deserialize, TypeUuid)]
+#[uuid = "5b8a777b-d238-4365-abc0-142686911af5"]
+pub struct Gold {
+    pub amount: i32,
+}
+
+impl Gold {
+    #[must_use]
+    pub const fn new(amount: i32) -> Self {
+        Gold { amount }
+    }
+}
+
+/// Base price of an item in gold pieces, before enchantments are taken into account
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "65bfd17c-7835-40ba-838b-f0e7c2cb9a5c"]
+pub struct Value {
+    pub gold: i32,
+}
+
+impl Value {
+    #[must_use]
+    pub const fn new(gold: i32) -> Self {
+        Value { gold }
+    }
+
+    /// Enchanted items are worth more, cursed ones are worth less
+    #[must_use]
+    pub fn price(&self, enchantment_level: i32) -> i32 {
+        i32::max(1, self.gold + self.gold * enchantment_level / 2)
+    }
+
+    /// Vendors only pay half of what they'd ask for the same item
+    #[must_use]
+    pub fn sell_price(&self, enchantment_level: i32) -> i32 {
+        i32::max(1, self.price(enchantment_level) / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::Value;
+
+    #[test]
+    fn enchantment_changes_the_price() {
+        let value = Value::new(10);
+        assert_eq!((value.price(0), value.sell_price(0)), (10, 5));
+        assert_eq!((value.price(2), value.sell_price(2)), (20, 10));
+        assert_eq!((value.price(-1), value.sell_price(-1)), (5, 2));
+    }
+
+    #[test]
+    fn nothing_is_free() {
+        let value = Value::new(2);
+        assert_eq!((value.price(-2), value.sell_price(-2)), (1, 1));
+        assert_eq!((value.price(-3), value.sell_price(-3)), (1, 1));
+        assert_eq!(Value::new(1).sell_price(0), 1);
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum Appearance {
+    Scroll,
+    Potion,
+}
+
+/// Items whose true `Name` is hidden behind a per-run randomized appearance until identified
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "21c83fef-0f44-41f9-b65f-a2481661849e"]
+pub struct Identifiable {
+    pub appearance: Appearance,
+}
+
+impl Identifiable {
+    #[must_use]
+    pub fn new(appearance: Appearance) -> Self {
+        Identifiable { appearance }
+    }
+}
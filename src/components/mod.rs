@@ -1,10 +1,15 @@
 pub use blocks_tile::*;
 pub use combat_stats::*;
+pub use description::*;
+pub use door::*;
 pub use effects::*;
+pub use enchantment::*;
 pub use entry_trigger::*;
 pub use equipment::*;
+pub use gold::*;
 pub use hidden::*;
 pub use hunger::*;
+pub use identifiable::*;
 pub use in_backpack::*;
 pub use item::*;
 pub use monster::*;
@@ -12,18 +17,26 @@ pub use name::*;
 pub use particle_lifetime::*;
 pub use player::*;
 pub use position::*;
+pub use quantity::*;
 pub use renderable::*;
 pub use serialize_me::*;
 pub use single_activation::*;
+pub use vendor::*;
 pub use viewshed::*;
+pub use weight::*;
 
 pub mod blocks_tile;
 pub mod combat_stats;
+pub mod description;
+pub mod door;
 pub mod effects;
+pub mod enchantment;
 pub mod entry_trigger;
 pub mod equipment;
+pub mod gold;
 pub mod hidden;
 pub mod hunger;
+pub mod identifiable;
 pub mod in_backpack;
 pub mod item;
 pub mod monster;
@@ -31,7 +44,60 @@ pub mod name;
 pub mod particle_lifetime;
 pub mod player;
 pub mod position;
+pub mod quantity;
 pub mod renderable;
 pub mod serialize_me;
 pub mod single_activation;
+pub mod vendor;
 pub mod viewshed;
+pub mod weight;
+
+/// Calls `$callback!` with the given arguments followed by every component that's saved and
+/// copied along with its entity, so that saving and duplicating can't disagree about the list
+macro_rules! foreach_component {
+    ($callback:ident!($($args:tt)*)) => {
+        $callback!(
+            $($args)*
+            AreaOfEffect,
+            BlocksTile,
+            Boss,
+            CombatStats,
+            Confusion,
+            Consumable,
+            Cursed,
+            DefenseBonus,
+            Description,
+            Door,
+            Enchantment,
+            EntryTrigger,
+            Equippable,
+            Equipped,
+            Gold,
+            Hidden,
+            HungerClock,
+            Identifiable,
+            Identifies,
+            InBackpack,
+            InflictsDamage,
+            Item,
+            MagicMapper,
+            MeleePowerBonus,
+            Monster,
+            Name,
+            Player,
+            Position,
+            ProvidesFood,
+            ProvidesHealing,
+            Quantity,
+            Ranged,
+            RemovesCurse,
+            Renderable,
+            SerializeMe,
+            SingleActivation,
+            Value,
+            Vendor,
+            Viewshed,
+            Weight
+        )
+    };
+}
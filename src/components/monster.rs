@@ -4,3 +4,8 @@ use type_uuid::TypeUuid;
 #[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
 #[uuid = "d72582fd-e492-4665-9d0d-7842b7029ef9"]
 pub struct Monster;
+
+/// Killing it wins the game
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "3f0c6d2e-8a1b-4c57-9e64-b2d7a0c5f913"]
+pub struct Boss;
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Stackable items carry how many of them the entity stands for
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "18ab4f99-a348-4b7a-ac23-382d98e5d0d1"]
+pub struct Quantity {
+    pub amount: i32,
+}
+
+impl Quantity {
+    #[must_use]
+    pub const fn new(amount: i32) -> Self {
+        Quantity { amount }
+    }
+}
+
+impl Default for Quantity {
+    fn default() -> Self {
+        Quantity::new(1)
+    }
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Bumping into a vendor opens the trade screen. Their stock is whatever they have `InBackpack`.
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "05676d5a-abb3-4538-a513-a90c317f55b3"]
+pub struct Vendor;
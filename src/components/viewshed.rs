@@ -24,4 +24,10 @@ impl Viewshed {
             range,
         }
     }
+
+    /// Whether something at `origin` can aim at `target`: it has to be visible and in `range`
+    #[must_use]
+    pub fn targetable(&self, origin: Position, range: i32, target: Position) -> bool {
+        self.visible_tiles.contains(&target) && origin.distance(target) <= range as f32
+    }
 }
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+use crate::components::Quantity;
+
+/// Backpack slots are selected by letter, so more than this wouldn't fit on the menu
+pub const MAX_BACKPACK_SLOTS: usize = 26;
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "e5afb5cf-f54d-4623-8a40-46db99eef163"]
+pub struct Weight {
+    pub weight: f32,
+}
+
+impl Weight {
+    #[must_use]
+    pub const fn new(weight: f32) -> Self {
+        Weight { weight }
+    }
+
+    /// Weight of the whole stack, if the item is stackable
+    pub fn total(&self, maybe_quantity: Option<&Quantity>) -> f32 {
+        self.weight * maybe_quantity.map_or(1, |quantity| quantity.amount) as f32
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Encumbrance {
+    Unburdened,
+    Burdened,
+    Overloaded,
+}
+
+impl Encumbrance {
+    pub fn new(carried: f32, capacity: f32) -> Self {
+        if carried > capacity * 1.5 {
+            Encumbrance::Overloaded
+        } else if carried > capacity {
+            Encumbrance::Burdened
+        } else {
+            Encumbrance::Unburdened
+        }
+    }
+
+    /// Nobody can carry more than this, no matter how hard they try
+    pub fn max_load(capacity: f32) -> f32 {
+        capacity * 2.0
+    }
+
+    pub fn melee_power_penalty(self) -> i32 {
+        match self {
+            Encumbrance::Unburdened => 0,
+            Encumbrance::Burdened => 1,
+            Encumbrance::Overloaded => 2,
+        }
+    }
+
+    pub fn defense_penalty(self) -> i32 {
+        match self {
+            Encumbrance::Overloaded => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::{Encumbrance, Quantity, Weight};
+
+    #[test]
+    fn stacks_weigh_by_quantity() {
+        let weight = Weight::new(0.5);
+        assert_eq!(weight.total(None), 0.5);
+        assert_eq!(weight.total(Some(&Quantity::new(1))), 0.5);
+        assert_eq!(weight.total(Some(&Quantity::new(4))), 2.0);
+    }
+
+    #[test]
+    fn encumbrance_thresholds() {
+        let capacity = 30.0;
+        assert_eq!(Encumbrance::new(0.0, capacity), Encumbrance::Unburdened);
+        assert_eq!(Encumbrance::new(30.0, capacity), Encumbrance::Unburdened);
+        assert_eq!(Encumbrance::new(30.5, capacity), Encumbrance::Burdened);
+        assert_eq!(Encumbrance::new(45.0, capacity), Encumbrance::Burdened);
+        assert_eq!(Encumbrance::new(45.5, capacity), Encumbrance::Overloaded);
+        assert_eq!(Encumbrance::max_load(capacity), 60.0);
+    }
+}
@@ -9,16 +9,21 @@ use legion::{query::component, IntoQuery, Resources, Schedule, World};
 use crate::cause_and_effect::{cae_clear_system, cae_debug_system, CauseAndEffect};
 use crate::{
     components::{Player, Position, Viewshed},
+    mapgen::MapgenSettings,
     resources::{
-        FrameData, GameLog, Input, Layout, Map, RexAssets, RunState, RunStateQueue, ShownInventory,
+        Autopilot, Clock, FrameData, GameLog, Input, ItemKnowledge, Keymap, Layout, LogKind,
+        LogRecord, Map, RexAssets, RunState, RunStateQueue, ShownInventory, Statistics,
     },
     systems::{
         ai::{ai_system, AiSystemState},
+        autopilot::{autopilot_system, AutopilotSystemState},
         damage::{damage_system, DamageSystemState},
         death::{death_system, DeathSystemState},
+        door::{door_system, DoorSystemState},
         entity_cleanup::{entity_cleanup_system, EntityCleanupRequest},
         game_log::{game_log_system, GameLogSystemState},
         hunger::{hunger_system, HungerSystemState},
+        identification::{identification_system, IdentificationSystemState},
         item_collection::{item_collection_system, ItemCollectionSystemState},
         item_drop::{item_drop_system, ItemDropSystemState},
         item_remove::{item_remove_system, ItemRemoveSystemState},
@@ -31,6 +36,8 @@ use crate::{
         particle::{particle_system, ParticleSystemState},
         player_action::player_action_system,
         render::render_system,
+        search::{search_system, SearchSystemState},
+        trade::{trade_system, TradeSystemState},
         trigger::{trigger_system, TriggerSystemState},
         turn::turn_system,
         visibility::visibility_system,
@@ -45,17 +52,26 @@ bracket_terminal::add_wasm_support!();
 
 #[macro_use]
 mod cause_and_effect;
+#[macro_use]
 mod components;
 mod mapgen;
 mod resources;
 mod systems;
 mod util;
 
+/// Just enough of map generation for `mapgen_stats` to run the builders outside of the game
+pub mod mapgen_api {
+    pub use crate::components::Name;
+    pub use crate::mapgen::{builder_chain, BuilderKind, MapBuilder, MapgenSettings};
+    pub use crate::resources::{RexAssets, TileType};
+}
+
 #[derive(Eq, PartialEq, Hash, Clone)]
 enum ScheduleType {
     Main,
     PlayerAction,
     Mapgen,
+    Trade,
     Load,
     RenderOnly,
 }
@@ -87,7 +103,11 @@ impl State {
         });
         insert_default_resources!(self.resources, [
             GameLog,
+            Clock,
+            Statistics,
+            ItemKnowledge,
             ShownInventory,
+            Autopilot,
             SegQueue<EntityCleanupRequest>,
             RexAssets,
             RunStateQueue
@@ -131,9 +151,9 @@ impl GameState for State {
         let maybe_new_runstate = match runstate {
             RunState::PreRun => {
                 self.reset();
-                self.resources.insert(GameLog {
-                    entries: vec!["Welcome to Rusty Roguelike".to_string()],
-                });
+                let mut game_log = GameLog::default();
+                game_log.push(0, LogRecord::new(LogKind::Welcome));
+                self.resources.insert(game_log);
                 self.execute(ScheduleType::Mapgen);
                 NewRunState::PushBack(RunState::AwaitingInput)
             }
@@ -146,8 +166,13 @@ impl GameState for State {
             | RunState::ShowInventory
             | RunState::ShowDropItem
             | RunState::ShowRemoveItem
+            | RunState::ShowVendor { .. }
             | RunState::MainMenu { .. }
-            | RunState::ShowTargeting { .. } => {
+            | RunState::ShowTargeting { .. }
+            | RunState::ShowLog { .. }
+            | RunState::ShowCharacter
+            | RunState::ShowLook { .. }
+            | RunState::ShowKeymap { .. } => {
                 self.execute(ScheduleType::PlayerAction);
                 NewRunState::None
             }
@@ -159,6 +184,11 @@ impl GameState for State {
                 self.execute(ScheduleType::Main);
                 NewRunState::PushBack(RunState::AwaitingInput)
             }
+            RunState::ResolveTrade { vendor, mode } => {
+                // Trading doesn't take a turn, but does need to log what happened
+                self.execute(ScheduleType::Trade);
+                NewRunState::PushBack(RunState::ShowVendor { vendor, mode })
+            }
             RunState::SaveGame => {
                 saveload::save(&self.world, &self.resources);
                 self.reset();
@@ -170,7 +200,7 @@ impl GameState for State {
                 self.execute(ScheduleType::Load);
                 NewRunState::PushBack(RunState::AwaitingInput)
             }
-            RunState::GameOver => {
+            RunState::GameOver | RunState::Victory => {
                 self.reset();
                 self.execute(ScheduleType::PlayerAction);
                 NewRunState::None
@@ -262,6 +292,7 @@ pub fn main() -> BError {
             .add_system(ai_system(AiSystemState::new(&resources)))
             .flush()
             .add_system(movement_system(MovementSystemState::new(&resources)))
+            .add_system(door_system(DoorSystemState::new(&resources)))
             .add_system(trigger_system(TriggerSystemState::new(&resources)))
             .flush()
             .add_system(visibility_system())
@@ -273,11 +304,16 @@ pub fn main() -> BError {
             .add_system(item_remove_system(ItemRemoveSystemState::new(&resources)))
             .add_system(melee_combat_system(MeleeCombatSystemState::new(&resources)))
             .flush()
+            .add_system(identification_system(IdentificationSystemState::new(
+                &resources,
+            )))
             .add_system(hunger_system(HungerSystemState::new(&resources)))
+            .add_system(search_system(SearchSystemState::new(&resources)))
             .flush()
             .add_system(damage_system(DamageSystemState::new(&resources)))
             .flush()
             .add_system(death_system(DeathSystemState::new(&resources)))
+            .add_system(autopilot_system(AutopilotSystemState::new(&resources)))
             .flush()
             .add_system(map_indexing_system())
             .add_system(particle_system(ParticleSystemState::new(&resources)))
@@ -307,6 +343,9 @@ pub fn main() -> BError {
         Schedule::builder()
             .add_system(mapgen_system())
             .flush()
+            .add_system(identification_system(IdentificationSystemState::new(
+                &resources,
+            )))
             .add_system(map_indexing_system())
             .add_system(visibility_system())
             .add_system(game_log_system(GameLogSystemState::new(&resources)))
@@ -314,6 +353,17 @@ pub fn main() -> BError {
             .add_system(cae_clear_system())
             .build(),
     );
+    schedules.insert(
+        ScheduleType::Trade,
+        Schedule::builder()
+            .add_system(trade_system(TradeSystemState::new(&resources)))
+            .flush()
+            .add_system(game_log_system(GameLogSystemState::new(&resources)))
+            .add_system(cae_debug_system())
+            .add_system(cae_clear_system())
+            .add_system(entity_cleanup_system())
+            .build(),
+    );
     schedules.insert(
         ScheduleType::Load,
         Schedule::builder()
@@ -347,6 +397,10 @@ pub fn main() -> BError {
     // Invoke RNG
     gs.resources.insert(RandomNumberGenerator::new());
 
+    // Keys survive across runs
+    gs.resources.insert(Keymap::load());
+    gs.resources.insert(MapgenSettings::default());
+
     // And go!
     gs.reset();
     main_loop(term, gs)
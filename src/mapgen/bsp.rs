@@ -2,10 +2,10 @@ use crate::systems::prelude::*;
 use petgraph::Graph;
 use rand::prelude::SliceRandom;
 use std::cmp::{max, min, Ordering};
-use std::collections::VecDeque;
 
-use super::{common::*, MapBuilder, SnapshotManager};
+use super::{common::*, BuildData, InitialMapBuilder};
 
+#[derive(Clone, Debug)]
 pub struct BspConfig {
     /// 0..=1; higher values lead to more varied room aspect ratios
     pub subdivision_variance: f64,
@@ -71,19 +71,16 @@ impl BspConfig {
 
 pub struct BspMapBuilder {
     config: BspConfig,
-    rooms: Vec<Rect>,
-    map: Map,
-    snapshot_manager: SnapshotManager,
 }
 
-impl MapBuilder for BspMapBuilder {
-    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+impl InitialMapBuilder for BspMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
         let mut graph = Graph::<Rect, ()>::new();
         let root = graph.add_node(Rect::with_size(
             0,
             0,
-            self.map.width - 1,
-            self.map.height - 1,
+            build_data.map.width - 1,
+            build_data.map.height - 1,
         ));
         let mut leaves = vec![root];
 
@@ -127,16 +124,18 @@ impl MapBuilder for BspMapBuilder {
                 .collect();
 
             if cfg!(feature = "visualize-mapgen") {
-                self.take_subdivision_snapshot(
+                Self::take_subdivision_snapshot(
                     leaves
                         .iter()
                         .map(|index| *graph.node_weight(*index).unwrap())
                         .collect(),
+                    build_data,
                 );
             }
         }
 
         // Create room in each partition
+        let mut rooms = vec![];
         for leaf in leaves {
             let partition = graph.node_weight(leaf).unwrap();
 
@@ -189,11 +188,11 @@ impl MapBuilder for BspMapBuilder {
             };
 
             let room = Rect::with_size(x1, y1, width, height);
-            apply_room_to_map(&room, &mut self.map);
-            walls_around(&room, &mut self.map);
-            self.rooms.push(room);
+            apply_room_to_map(&room, &mut build_data.map);
+            walls_around(&room, &mut build_data.map);
+            rooms.push(room);
         }
-        self.take_snapshot();
+        build_data.take_snapshot();
 
         // Add corridors
         for depth in (0..max_depth).rev() {
@@ -217,70 +216,40 @@ impl MapBuilder for BspMapBuilder {
                     connect_regions(
                         *graph.node_weight(children[i]).unwrap(),
                         *graph.node_weight(children[i + 1]).unwrap(),
-                        &mut self.map,
+                        &mut build_data.map,
                         rng,
                     );
-                    self.take_snapshot();
+                    build_data.take_snapshot();
                 }
             }
         }
 
         // Shuffle rooms so that starting, stairs positions are interesting
-        self.rooms.shuffle(rng.get_rng());
-
-        // Place stairs down in "last" room
-        let last_room = self.rooms.last().unwrap();
-        self.map[&last_room.center().into()] = TileType::DownStairs;
-    }
-
-    fn spawn_entities(&self, commands: &mut CommandBuffer, rng: &mut RandomNumberGenerator) {
-        for room in self.rooms.iter().skip(1) {
-            super::spawner::spawn_room(rng, room, self.map.depth, commands);
-        }
-    }
-
-    fn get_map(&self) -> Map {
-        self.map.clone()
-    }
-
-    fn get_starting_position(&self) -> Position {
-        self.rooms[0].center().into()
-    }
-
-    fn get_snapshots(&self) -> VecDeque<Map> {
-        self.snapshot_manager.get_snapshots()
+        rooms.shuffle(rng.get_rng());
+        build_data.rooms = Some(rooms);
     }
 }
 
 impl BspMapBuilder {
     #[must_use]
-    pub fn new(width: i32, height: i32, depth: i32, config: BspConfig) -> Self {
-        BspMapBuilder {
-            rooms: vec![],
-            map: Map::new(width, height, depth),
-            snapshot_manager: SnapshotManager::new(),
-            config,
-        }
+    pub fn new(config: BspConfig) -> Self {
+        BspMapBuilder { config }
     }
 
-    fn clear(&mut self, tile: TileType) {
-        for x in 0..self.map.width {
-            for y in 0..self.map.height {
-                self.map[&Position::new(x, y)] = tile;
+    fn clear(tile: TileType, map: &mut Map) {
+        for x in 0..map.width {
+            for y in 0..map.height {
+                map[&Position::new(x, y)] = tile;
             }
         }
     }
 
-    fn take_subdivision_snapshot(&mut self, rects: Vec<Rect>) {
-        self.clear(TileType::Floor);
+    fn take_subdivision_snapshot(rects: Vec<Rect>, build_data: &mut BuildData) {
+        Self::clear(TileType::Floor, &mut build_data.map);
         for rect in rects {
-            walls_around(&rect, &mut self.map);
+            walls_around(&rect, &mut build_data.map);
         }
-        self.take_snapshot();
-        self.clear(TileType::Wall);
-    }
-
-    fn take_snapshot(&mut self) {
-        self.snapshot_manager.record_snapshot(self.get_map());
+        build_data.take_snapshot();
+        Self::clear(TileType::Wall, &mut build_data.map);
     }
 }
@@ -7,7 +7,9 @@ use std::convert::TryInto;
 use crate::util::rect_ext::RectExt;
 use itertools::Itertools;
 use rand::distributions::uniform::UniformSampler;
+use rand::seq::{IteratorRandom, SliceRandom};
 use smallvec::alloc::collections::VecDeque;
+use strum::IntoEnumIterator;
 
 pub fn apply_room_to_map(room: &Rect, map: &mut Map) {
     fill(room, TileType::Floor, map);
@@ -249,8 +251,44 @@ pub fn walls_around(rect: &Rect, map: &mut Map) {
     }
 }
 
+/// Spots where a corridor squeezes into a room between two walls: perfect for a door
+pub fn door_positions(rooms: &[Rect], map: &Map) -> Vec<Position> {
+    let is_wall = |position: Position| map.get(position) == Some(TileType::Wall);
+    let is_walkable = |position: Position| map.get(position).map_or(false, |t| t != TileType::Wall);
+
+    let mut positions = vec![];
+    for room in rooms {
+        let surroundings = Rect::with_exact(room.x1 - 1, room.y1 - 1, room.x2 + 1, room.y2 + 1);
+        for point in surroundings.point_set() {
+            let position = Position::from(point);
+            if map.get(position) != Some(TileType::Floor) || positions.contains(&position) {
+                continue;
+            }
+            // Walls on two opposite sides, walkable tiles on the other two with only one of them
+            // being inside the room
+            let is_junction = [(0, 1), (1, 0)].iter().any(|&(dx, dy)| {
+                let wall_a = Position::new(position.x + dx, position.y + dy);
+                let wall_b = Position::new(position.x - dx, position.y - dy);
+                let open_a = Position::new(position.x + dy, position.y + dx);
+                let open_b = Position::new(position.x - dy, position.y - dx);
+                is_wall(wall_a)
+                    && is_wall(wall_b)
+                    && is_walkable(open_a)
+                    && is_walkable(open_b)
+                    && room.point_in_rect(*open_a) != room.point_in_rect(*open_b)
+            });
+            if is_junction {
+                positions.push(position);
+            }
+        }
+    }
+    positions
+}
+
+/// Everything reachable from `seed` on foot, or if `seed` itself can't be walked on, the blob of
+/// unwalkable tiles around it
 pub fn connected_region(seed: &Position, map: &Map) -> HashSet<Position> {
-    let target = map[seed];
+    let walkable = map[seed].is_walkable();
     let mut retval = HashSet::new();
     retval.insert(*seed);
 
@@ -265,7 +303,11 @@ pub fn connected_region(seed: &Position, map: &Map) -> HashSet<Position> {
                     continue;
                 }
                 let p_next = p + Vector::new(dx, dy);
-                if map.get(p_next) == Some(target) && !retval.contains(&p_next) {
+                if map
+                    .get(p_next)
+                    .map_or(false, |tile| tile.is_walkable() == walkable)
+                    && !retval.contains(&p_next)
+                {
                     retval.insert(p_next);
                     queue.push_back(p_next);
                 }
@@ -295,13 +337,154 @@ pub fn random_position_with_tile(
 pub fn remove_unreachable_areas(start: &Position, map: &mut Map) -> HashSet<Position> {
     let connected_region = connected_region(start, map);
     for position in map.position_set() {
-        if map[&position] != TileType::Wall && !connected_region.contains(&position) {
+        if map[&position].is_walkable() && !connected_region.contains(&position) {
             map[&position] = TileType::Wall;
         }
     }
     connected_region
 }
 
+/// Every walkable position, in a stable order so that seeded generation stays reproducible
+pub fn walkable_positions(map: &Map) -> Vec<Position> {
+    (0..map.height)
+        .flat_map(|y| (0..map.width).map(move |x| Position::new(x, y)))
+        .filter(|p| map[p].is_walkable())
+        .collect()
+}
+
+fn is_fully_connected(map: &Map) -> bool {
+    let walkable = walkable_positions(map);
+    walkable.first().map_or(true, |seed| {
+        connected_region(seed, map).len() == walkable.len()
+    })
+}
+
+/// Carve a meandering band of `tile` across the map, then bridge it where it can be crossed until
+/// everything walkable is connected again. Returns the positions that were carved, bridged or not.
+pub fn add_river(tile: TileType, map: &mut Map, rng: &mut RandomNumberGenerator) -> Vec<Position> {
+    let horizontal = rng.roll_dice(1, 2) == 1;
+    let (length, breadth) = if horizontal {
+        (map.width, map.height)
+    } else {
+        (map.height, map.width)
+    };
+    let at = |along: i32, across: i32| {
+        if horizontal {
+            Position::new(along, across)
+        } else {
+            Position::new(across, along)
+        }
+    };
+
+    let mut river = vec![];
+    // What was carved at each step along the river, from one bank to the other
+    let mut crossings = vec![];
+    let mut offset = rng.range(breadth / 4, breadth * 3 / 4);
+    for along in 0..length {
+        let previous = offset;
+        offset = max(1, min(breadth - 2, offset + rng.range(-1, 2)));
+        // Fill the whole step, otherwise the river can be crossed diagonally
+        let (near, far) = (min(previous, offset), max(previous, offset));
+        let crossing: Vec<Position> = (near..=far)
+            .map(|across| at(along, across))
+            .filter(|position| map[position].is_walkable())
+            .collect();
+        for position in &crossing {
+            map[position] = tile;
+        }
+        river.extend(&crossing);
+        // A bridge only helps if there's somewhere to walk on both ends
+        let walkable = |across| {
+            map.get(at(along, across))
+                .map_or(false, TileType::is_walkable)
+        };
+        let useful =
+            crossing.len() as i32 == far - near + 1 && walkable(near - 1) && walkable(far + 1);
+        crossings.push((!useful, crossing));
+    }
+
+    // Random useful crossings first, the rest only if nothing else reconnects the map
+    crossings.shuffle(rng.get_rng());
+    crossings.sort_by_key(|(useless, _)| *useless);
+    for (_, crossing) in crossings {
+        if is_fully_connected(map) {
+            break;
+        }
+        for position in crossing {
+            map[&position] = TileType::Bridge;
+        }
+    }
+
+    river
+}
+
+/// Turn some of the floor next to deep water into shallows
+pub fn add_banks(river: &[Position], map: &mut Map, rng: &mut RandomNumberGenerator) {
+    for position in river {
+        if map[position] != TileType::DeepWater {
+            continue;
+        }
+        for heading in Heading::iter() {
+            let bank = *position + Vector::unit(heading);
+            if map.get(bank) == Some(TileType::Floor) && rng.roll_dice(1, 2) == 1 {
+                map[&bank] = TileType::ShallowWater;
+            }
+        }
+    }
+}
+
+/// Grow `count` blobs of `tile` over plain floor by stumbling around from random starting points
+pub fn add_patches(
+    tile: TileType,
+    count: i32,
+    size: i32,
+    map: &mut Map,
+    rng: &mut RandomNumberGenerator,
+) {
+    for _ in 0..count {
+        let mut position = random_position_with_tile(TileType::Floor, map, rng);
+        for _ in 0..size {
+            if map[&position] == TileType::Floor {
+                map[&position] = tile;
+            }
+            let direction = Heading::iter().choose(rng.get_rng()).unwrap();
+            let candidate = position + Vector::unit(direction);
+            if map.get(candidate).map_or(false, TileType::is_walkable) {
+                position = candidate;
+            }
+        }
+    }
+}
+
+/// Sprinkle `tile` over roughly `percent`% of the plain floor
+pub fn scatter(tile: TileType, percent: i32, map: &mut Map, rng: &mut RandomNumberGenerator) {
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let position = Position::new(x, y);
+            if map[&position] == TileType::Floor && rng.roll_dice(1, 100) <= percent {
+                map[&position] = tile;
+            }
+        }
+    }
+}
+
+/// Dress up a cave with water, grass and, deeper down, lava or chasms.
+/// Expects all the walkable tiles to be connected, and keeps them that way.
+pub fn add_natural_features(map: &mut Map, rng: &mut RandomNumberGenerator) {
+    if rng.roll_dice(1, 2) == 1 {
+        let river = add_river(TileType::DeepWater, map, rng);
+        add_banks(&river, map, rng);
+    }
+    if map.depth >= 3 && rng.roll_dice(1, 3) == 1 {
+        add_river(TileType::Lava, map, rng);
+    } else if rng.roll_dice(1, 4) == 1 {
+        add_river(TileType::Chasm, map, rng);
+    }
+    let patches = rng.range(3, 7);
+    add_patches(TileType::Grass, patches, 40, map, rng);
+    scatter(TileType::Rubble, 2, map, rng);
+}
+
 pub fn generate_voronoi_spawn_regions(
     map: &Map,
     rng: &mut RandomNumberGenerator,
@@ -335,6 +518,8 @@ pub fn find_furthest_reachable_tiles(
         .map
         .iter()
         .enumerate()
+        // Unreachable tiles are left at f32::MAX
+        .filter(|(_idx, distance)| **distance < std::f32::MAX)
         .sorted_by(|a, b| b.1.partial_cmp(a.1).unwrap_or(Ordering::Equal))
         .map(|(idx, _distance)| map.idx_pos(idx))
         .take(count)
@@ -346,35 +531,30 @@ mod tests {
     use crate::mapgen::common::*;
 
     fn make_map() -> Map {
-        /*
-         01234567890123456789
-        0####################
-        1####################
-        2####################
-        3###############...##
-        4###############...##
-        5#####......####...##
-        6#####.............##
-        7#####......####...##
-        8#####......####...##
-        9#####......####...##
-        0#####......####...##
-        1####################
-        2####################
-        3####################
-        4####################
-        5##.................#
-        6##.................#
-        7##.................#
-        8##.................#
-        9####################
-        */
-        let mut map = Map::new(20, 20, 1);
-        apply_room_to_map(&Rect::with_exact(5, 5, 11, 11), &mut map);
-        apply_room_to_map(&Rect::with_exact(15, 3, 18, 11), &mut map);
-        apply_room_to_map(&Rect::with_exact(2, 17, 19, 19), &mut map);
-        apply_horizontal_tunnel(&mut map, 10, 15, 6);
-        map
+        Map::from_ascii(
+            "
+            ####################
+            ####################
+            ####################
+            ###############...##
+            ###############...##
+            #####......####...##
+            #####.............##
+            #####......####...##
+            #####......####...##
+            #####......####...##
+            #####......####...##
+            ####################
+            ####################
+            ####################
+            ####################
+            ####################
+            ####################
+            ##.................#
+            ##.................#
+            ####################
+            ",
+        )
     }
 
     #[test]
@@ -469,6 +649,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_door_positions() {
+        let map = make_map();
+        let rooms = vec![
+            Rect::with_exact(5, 5, 11, 11),
+            Rect::with_exact(15, 3, 18, 11),
+            Rect::with_exact(2, 17, 19, 19),
+        ];
+        assert_eq!(
+            door_positions(&rooms, &map),
+            vec![Position::new(11, 6), Position::new(14, 6)]
+        );
+    }
+
     #[test]
     fn test_connect_regions_direct_vertical() {
         let mut map = make_map();
@@ -491,4 +685,47 @@ mod tests {
         let map = make_map();
         assert_eq!(connected_region(&Position::new(7, 7), &map).len(), 64);
     }
+
+    #[test]
+    fn test_connected_region_follows_walkable_tiles() {
+        let mut map = Map::from_ascii(
+            r##"
+            ##########
+            #.~="#≈.>#
+            #,########
+            ##########
+            "##,
+        );
+        assert_eq!(connected_region(&Position::new(1, 1), &map).len(), 5);
+        assert_eq!(connected_region(&Position::new(7, 1), &map).len(), 2);
+        // Starting from a wall finds every unwalkable tile it touches, deep water included
+        assert_eq!(connected_region(&Position::new(6, 1), &map).len(), 33);
+
+        remove_unreachable_areas(&Position::new(1, 1), &mut map);
+        assert_eq!(map[(3, 1)], TileType::Bridge);
+        assert_eq!(map[(1, 2)], TileType::Rubble);
+        assert_eq!(map[(8, 1)], TileType::Wall);
+    }
+
+    #[test]
+    fn test_river_keeps_map_connected() {
+        let mut rng = RandomNumberGenerator::seeded(42);
+        for tile in &[TileType::DeepWater, TileType::Lava, TileType::Chasm] {
+            let mut map = Map::new(20, 20, 1);
+            apply_room_to_map(&Rect::with_exact(1, 1, 19, 19), &mut map);
+            let walkable_before = walkable_positions(&map).len();
+            let river = add_river(*tile, &mut map, &mut rng);
+
+            assert!(!river.is_empty());
+            // One crossing is enough in an open room
+            let bridges = river
+                .iter()
+                .filter(|position| map[*position] == TileType::Bridge)
+                .count();
+            assert!(bridges > 0 && bridges <= 2, "{} bridges", bridges);
+            let walkable = walkable_positions(&map);
+            assert!(walkable.len() < walkable_before);
+            assert_eq!(connected_region(&walkable[0], &map).len(), walkable.len());
+        }
+    }
 }
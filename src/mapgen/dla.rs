@@ -0,0 +1,229 @@
+use crate::systems::prelude::*;
+
+use rand::seq::IteratorRandom;
+use strum::IntoEnumIterator;
+
+use super::{BuildData, InitialMapBuilder};
+
+#[derive(Clone, Copy)]
+pub enum DlaAlgorithm {
+    /// Diggers start anywhere and stumble around until they bump into the aggregate
+    WalkInwards,
+    /// Diggers start in the middle and stumble around until they leave the aggregate
+    WalkOutwards,
+    /// Diggers start anywhere and head straight for the middle
+    CentralAttractor,
+}
+
+/// Whatever gets carved is mirrored across the middle of the map
+#[derive(Clone, Copy)]
+pub enum DlaSymmetry {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+pub struct DlaConfig {
+    pub algorithm: DlaAlgorithm,
+    pub symmetry: DlaSymmetry,
+    /// 0 carves single tiles, 1 carves 3x3 squares, and so on
+    pub brush_radius: i32,
+    /// Stop once this much of the map is floor
+    pub floor_percent: f32,
+}
+
+impl DlaConfig {
+    pub fn walk_inwards() -> Self {
+        DlaConfig {
+            algorithm: DlaAlgorithm::WalkInwards,
+            symmetry: DlaSymmetry::None,
+            brush_radius: 0,
+            floor_percent: 0.25,
+        }
+    }
+
+    pub fn walk_outwards() -> Self {
+        DlaConfig {
+            algorithm: DlaAlgorithm::WalkOutwards,
+            symmetry: DlaSymmetry::None,
+            brush_radius: 1,
+            floor_percent: 0.25,
+        }
+    }
+
+    pub fn central_attractor() -> Self {
+        DlaConfig {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            symmetry: DlaSymmetry::None,
+            brush_radius: 1,
+            floor_percent: 0.25,
+        }
+    }
+
+    pub fn insectoid() -> Self {
+        DlaConfig {
+            algorithm: DlaAlgorithm::CentralAttractor,
+            symmetry: DlaSymmetry::Horizontal,
+            brush_radius: 1,
+            floor_percent: 0.25,
+        }
+    }
+}
+
+/// Diffusion-limited aggregation: grows a cave out from the middle of the map, one digger at a
+/// time. Everything it carves is connected.
+pub struct DlaMapBuilder {
+    config: DlaConfig,
+}
+
+impl DlaMapBuilder {
+    #[must_use]
+    pub fn new(config: DlaConfig) -> Self {
+        DlaMapBuilder { config }
+    }
+
+    fn center(map: &Map) -> Position {
+        Position::new(map.width / 2, map.height / 2)
+    }
+
+    /// Diggers stay off the edges of the map. The area is symmetric around the center, so that
+    /// mirroring never lands out of bounds (which could leave the mirror images disconnected).
+    fn in_bounds(position: Position, map: &Map) -> bool {
+        let center = Self::center(map);
+        (position.x - center.x).abs() < center.x - 1 && (position.y - center.y).abs() < center.y - 1
+    }
+
+    fn random_position(map: &Map, rng: &mut RandomNumberGenerator) -> Position {
+        let center = Self::center(map);
+        Position::new(
+            rng.range(2, 2 * center.x - 1),
+            rng.range(2, 2 * center.y - 1),
+        )
+    }
+
+    fn stumble(position: Position, map: &Map, rng: &mut RandomNumberGenerator) -> Position {
+        let direction = Heading::iter().choose(rng.get_rng()).unwrap();
+        let candidate = position + Vector::unit(direction);
+        if Self::in_bounds(candidate, map) {
+            candidate
+        } else {
+            position
+        }
+    }
+
+    /// Carve around `position` and its mirror images. Returns how many tiles were carved.
+    fn paint(&self, position: Position, map: &mut Map) -> usize {
+        let center = Self::center(map);
+        let mirror_x = Position::new(2 * center.x - position.x, position.y);
+        let mirror_y = Position::new(position.x, 2 * center.y - position.y);
+        let mirror_xy = Position::new(mirror_x.x, mirror_y.y);
+        let targets = match self.config.symmetry {
+            DlaSymmetry::None => vec![position],
+            DlaSymmetry::Horizontal => vec![position, mirror_x],
+            DlaSymmetry::Vertical => vec![position, mirror_y],
+            DlaSymmetry::Both => vec![position, mirror_x, mirror_y, mirror_xy],
+        };
+
+        let radius = self.config.brush_radius;
+        let mut carved = 0;
+        for target in targets {
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let p = target + Vector::new(dx, dy);
+                    if Self::in_bounds(p, map) && map[&p] == TileType::Wall {
+                        map[&p] = TileType::Floor;
+                        carved += 1;
+                    }
+                }
+            }
+        }
+        carved
+    }
+}
+
+impl InitialMapBuilder for DlaMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        // Seed the aggregate with a small cross in the middle
+        let center = Self::center(&build_data.map);
+        build_data.map[&center] = TileType::Floor;
+        for heading in Heading::iter() {
+            build_data.map[&(center + Vector::unit(heading))] = TileType::Floor;
+        }
+        build_data.take_snapshot();
+
+        let target = ((build_data.map.width * build_data.map.height) as f32
+            * self.config.floor_percent) as usize;
+        let mut floor_count = 5;
+        let mut iteration = 0;
+        while floor_count < target {
+            let map = &build_data.map;
+            let dig_at = match self.config.algorithm {
+                DlaAlgorithm::WalkInwards => {
+                    let mut digger = Self::random_position(map, rng);
+                    let mut previous = digger;
+                    while map[&digger] == TileType::Wall {
+                        previous = digger;
+                        digger = Self::stumble(digger, map, rng);
+                    }
+                    previous
+                }
+                DlaAlgorithm::WalkOutwards => {
+                    let mut digger = center;
+                    while map[&digger] != TileType::Wall {
+                        digger = Self::stumble(digger, map, rng);
+                    }
+                    digger
+                }
+                DlaAlgorithm::CentralAttractor => {
+                    let start = Self::random_position(map, rng);
+                    let mut previous = start;
+                    for point in line2d(LineAlg::Bresenham, *start, *center) {
+                        if map[&Position::from(point)] != TileType::Wall {
+                            break;
+                        }
+                        previous = Position::from(point);
+                    }
+                    previous
+                }
+            };
+            floor_count += self.paint(dig_at, &mut build_data.map);
+
+            iteration += 1;
+            if iteration % 50 == 0 {
+                build_data.take_snapshot();
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::common::*;
+    use crate::mapgen::dla::*;
+
+    #[test]
+    fn test_dla_is_connected() {
+        for (seed, config) in vec![
+            DlaConfig::walk_inwards(),
+            DlaConfig::walk_outwards(),
+            DlaConfig::central_attractor(),
+            DlaConfig::insectoid(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut rng = RandomNumberGenerator::seeded(seed as u64);
+            let mut build_data = BuildData::new(80, 43, 1);
+            DlaMapBuilder::new(config).build_map(&mut rng, &mut build_data);
+
+            let walkable = walkable_positions(&build_data.map);
+            assert!(walkable.len() >= 80 * 43 / 4);
+            assert_eq!(
+                connected_region(&walkable[0], &build_data.map).len(),
+                walkable.len()
+            );
+        }
+    }
+}
@@ -1,7 +1,6 @@
 use crate::systems::prelude::*;
-use std::collections::VecDeque;
 
-use super::{common::*, MapBuilder, SnapshotManager};
+use super::{common::*, BuildData, InitialMapBuilder};
 
 const TOP: usize = 0;
 const RIGHT: usize = 1;
@@ -117,7 +116,7 @@ impl<'a> Grid<'a> {
         None
     }
 
-    fn generate_maze(&mut self, generator: &mut MazeMapBuilder) {
+    fn generate_maze(&mut self, build_data: &mut BuildData) {
         let mut i = 0;
         loop {
             self.cells[self.current].visited = true;
@@ -145,8 +144,8 @@ impl<'a> Grid<'a> {
             }
 
             if i % 50 == 0 {
-                self.copy_to_map(&mut generator.map);
-                generator.take_snapshot();
+                self.copy_to_map(&mut build_data.map);
+                build_data.take_snapshot();
             }
             i += 1;
         }
@@ -176,68 +175,11 @@ impl<'a> Grid<'a> {
     }
 }
 
-pub struct MazeMapBuilder {
-    map: Map,
-    snapshot_manager: SnapshotManager,
-    // We just pick a random empty spot, so need to store it for it to be stable
-    cached_starting_position: Option<Position>,
-}
-
-impl MapBuilder for MazeMapBuilder {
-    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
-        self.build_maze(rng);
-    }
-
-    fn spawn_entities(&self, _commands: &mut CommandBuffer, _rng: &mut RandomNumberGenerator) {
-        // mazes are empty for now
-    }
-
-    fn get_map(&self) -> Map {
-        self.map.clone()
-    }
-
-    fn get_starting_position(&self) -> Position {
-        self.cached_starting_position.unwrap()
-    }
-
-    fn get_snapshots(&self) -> VecDeque<Map> {
-        self.snapshot_manager.get_snapshots()
-    }
-}
-
-impl MazeMapBuilder {
-    #[must_use]
-    pub fn new(width: i32, height: i32, depth: i32) -> Self {
-        MazeMapBuilder {
-            map: Map::new(width, height, depth),
-            snapshot_manager: SnapshotManager::new(),
-            cached_starting_position: None,
-        }
-    }
-
-    fn take_snapshot(&mut self) {
-        self.snapshot_manager.record_snapshot(self.get_map());
-    }
+pub struct MazeMapBuilder;
 
-    fn build_maze(&mut self, rng: &mut RandomNumberGenerator) {
-        let mut grid = Grid::new(self.map.width / 2, self.map.height / 2, rng);
-        grid.generate_maze(self);
-
-        // Pick a random starting position
-        let starting_position = random_position_with_tile(TileType::Floor, &self.map, rng);
-        self.cached_starting_position = Some(starting_position);
-
-        // Pick a random position for the downwards staircase that's pretty far from the player
-        let dijsktra_map = bracket_lib::prelude::DijkstraMap::new(
-            self.map.width,
-            self.map.height,
-            &[self.map.pos_idx(starting_position)],
-            &self.map,
-            200.0,
-        );
-        let furthest_reachable_tiles = find_furthest_reachable_tiles(&self.map, &dijsktra_map, 20);
-        let stairs_down_pos = rng.random_slice_entry(&furthest_reachable_tiles).unwrap();
-        self.map[stairs_down_pos] = TileType::DownStairs;
-        self.take_snapshot();
+impl InitialMapBuilder for MazeMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let mut grid = Grid::new(build_data.map.width / 2, build_data.map.height / 2, rng);
+        grid.generate_maze(build_data);
     }
 }
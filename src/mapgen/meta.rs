@@ -0,0 +1,179 @@
+use crate::systems::prelude::*;
+
+use super::{common::*, spawner, BuildData, MetaMapBuilder};
+
+/// Start in the middle of the first room
+pub struct RoomBasedStartingPosition;
+
+impl MetaMapBuilder for RoomBasedStartingPosition {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rooms = build_data
+            .rooms
+            .as_ref()
+            .expect("RoomBasedStartingPosition requires a builder with rooms");
+        build_data.starting_position = Some(rooms[0].center().into());
+    }
+}
+
+/// Start on a random floor tile
+pub struct RandomStartingPosition;
+
+impl MetaMapBuilder for RandomStartingPosition {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        build_data.starting_position = Some(random_position_with_tile(
+            TileType::Floor,
+            &build_data.map,
+            rng,
+        ));
+    }
+}
+
+/// Wall off everything that can't be walked to from the starting position
+pub struct CullUnreachable;
+
+impl MetaMapBuilder for CullUnreachable {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let starting_position = build_data
+            .starting_position
+            .expect("CullUnreachable requires a starting position");
+        remove_unreachable_areas(&starting_position, &mut build_data.map);
+        build_data.take_snapshot();
+    }
+}
+
+/// Place the stairs down in the middle of the last room
+pub struct RoomBasedStairs;
+
+impl MetaMapBuilder for RoomBasedStairs {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let last_room = *build_data
+            .rooms
+            .as_ref()
+            .and_then(|rooms| rooms.last())
+            .expect("RoomBasedStairs requires a builder with rooms");
+        build_data.map[&last_room.center().into()] = TileType::DownStairs;
+        build_data.take_snapshot();
+    }
+}
+
+/// Place the stairs down on a random position that's pretty far from the player
+pub struct DistantStairs;
+
+impl MetaMapBuilder for DistantStairs {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let starting_position = build_data
+            .starting_position
+            .expect("DistantStairs requires a starting position");
+        let map = &build_data.map;
+        let dijsktra_map = DijkstraMap::new(
+            map.width,
+            map.height,
+            &[map.pos_idx(starting_position)],
+            map,
+            200.0,
+        );
+        let furthest_reachable_tiles = find_furthest_reachable_tiles(map, &dijsktra_map, 20);
+        let stairs_down_pos = *rng.random_slice_entry(&furthest_reachable_tiles).unwrap();
+        build_data.map[&stairs_down_pos] = TileType::DownStairs;
+        build_data.take_snapshot();
+    }
+}
+
+/// Put the boss somewhere far from the player. There are no stairs on the boss level.
+pub struct BossPlacement;
+
+impl MetaMapBuilder for BossPlacement {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let starting_position = build_data
+            .starting_position
+            .expect("BossPlacement requires a starting position");
+        let map = &build_data.map;
+        let dijsktra_map = DijkstraMap::new(
+            map.width,
+            map.height,
+            &[map.pos_idx(starting_position)],
+            map,
+            200.0,
+        );
+        let furthest_reachable_tiles = find_furthest_reachable_tiles(map, &dijsktra_map, 20);
+        let boss_position = *rng.random_slice_entry(&furthest_reachable_tiles).unwrap();
+        for region in build_data.spawn_regions.iter_mut() {
+            region.retain(|position| *position != boss_position);
+        }
+        build_data
+            .spawn_list
+            .push((boss_position, spawner::orc_warlord));
+    }
+}
+
+/// Spawn into every room except the one the player starts in
+pub struct RoomBasedSpawning;
+
+impl MetaMapBuilder for RoomBasedSpawning {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let starting_position = build_data.starting_position;
+        let rooms = build_data
+            .rooms
+            .as_ref()
+            .expect("RoomBasedSpawning requires a builder with rooms");
+        let regions: Vec<Vec<Position>> = rooms
+            .iter()
+            .filter(|room| starting_position.map_or(true, |p| !room.point_in_rect(*p)))
+            .map(|room| room.point_set().iter().map(|&p| p.into()).collect())
+            .collect();
+        build_data.spawn_regions = regions;
+    }
+}
+
+/// Spawn into areas generated with Voronoi noise, for levels without rooms
+pub struct AreaBasedSpawning;
+
+impl MetaMapBuilder for AreaBasedSpawning {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let areas = generate_voronoi_spawn_regions(&build_data.map, rng);
+        build_data.spawn_regions = areas.into_iter().map(|(_, area)| area).collect();
+    }
+}
+
+/// Find the spots where doors would fit between rooms and corridors
+pub struct DoorPlacement;
+
+impl MetaMapBuilder for DoorPlacement {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let rooms = build_data
+            .rooms
+            .as_ref()
+            .expect("DoorPlacement requires a builder with rooms");
+        build_data.door_positions = door_positions(rooms, &build_data.map);
+    }
+}
+
+/// Rivers, lava, chasms, grass and rubble; see `add_natural_features`
+pub struct NaturalFeatures;
+
+impl MetaMapBuilder for NaturalFeatures {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        add_natural_features(&mut build_data.map, rng);
+        build_data.take_snapshot();
+    }
+}
+
+/// Sprinkle some `tile` over the floor
+pub struct Scatter {
+    tile: TileType,
+    percent: i32,
+}
+
+impl Scatter {
+    #[must_use]
+    pub fn new(tile: TileType, percent: i32) -> Self {
+        Scatter { tile, percent }
+    }
+}
+
+impl MetaMapBuilder for Scatter {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        scatter(self.tile, self.percent, &mut build_data.map, rng);
+        build_data.take_snapshot();
+    }
+}
@@ -1,22 +1,43 @@
-use bracket_lib::prelude::RandomNumberGenerator;
+use bracket_lib::prelude::{RandomNumberGenerator, Rect};
+use itertools::Itertools;
 use std::collections::VecDeque;
+use strum_macros::{Display, EnumIter};
 
 use crate::components::Position;
 use crate::mapgen::bsp::{BspConfig, BspMapBuilder};
 use crate::mapgen::cellular_automata::{CellularAutomataMapBuilder, DefaultCellularAutomataConfig};
+use crate::mapgen::dla::{DlaConfig, DlaMapBuilder};
 use crate::mapgen::drunkards_walk::DrunkardsWalkMapBuilder;
 use crate::mapgen::maze::MazeMapBuilder;
+use crate::mapgen::meta::{
+    AreaBasedSpawning, BossPlacement, CullUnreachable, DistantStairs, DoorPlacement,
+    NaturalFeatures, RandomStartingPosition, RoomBasedSpawning, RoomBasedStairs,
+    RoomBasedStartingPosition, Scatter,
+};
+use crate::mapgen::prefab::{Prefab, PrefabLevelBuilder, PrefabVaults};
 use crate::mapgen::simple::SimpleMapBuilder;
-use crate::resources::Map;
+use crate::mapgen::spawner::Spawner;
+use crate::mapgen::themes::Theme;
+use crate::mapgen::validation::{MapValidator, Rejection};
+use crate::mapgen::voronoi::VoronoiMapBuilder;
+use crate::mapgen::wfc::WaveFunctionCollapseBuilder;
+use crate::resources::{Map, RexAssets, TileType, ORC_OUTPOST_DEPTH};
 use crate::systems::prelude::CommandBuffer;
 
 mod bsp;
 mod cellular_automata;
 mod common;
+mod dla;
 mod drunkards_walk;
 mod maze;
+mod meta;
+mod prefab;
 mod simple;
 pub mod spawner;
+pub mod themes;
+mod validation;
+mod voronoi;
+mod wfc;
 
 struct SnapshotManager {
     snapshots: VecDeque<Map>,
@@ -49,33 +70,517 @@ pub trait MapBuilder {
     fn get_snapshots(&self) -> VecDeque<Map>;
 }
 
-pub fn random_builder(
+/// Everything the stages of a `BuilderChain` know about the level being built
+pub struct BuildData {
+    pub map: Map,
+    pub starting_position: Option<Position>,
+    /// Only set by builders that think in terms of rooms
+    pub rooms: Option<Vec<Rect>>,
+    /// Each region gets its own roll on the spawn table
+    pub spawn_regions: Vec<Vec<Position>>,
+    pub door_positions: Vec<Position>,
+    /// Things that must show up at exact positions, like the contents of prefabs
+    pub spawn_list: Vec<(Position, Spawner)>,
+    snapshot_manager: SnapshotManager,
+}
+
+impl BuildData {
+    #[must_use]
+    fn new(width: i32, height: i32, depth: i32) -> Self {
+        BuildData {
+            map: Map::new(width, height, depth),
+            starting_position: None,
+            rooms: None,
+            spawn_regions: vec![],
+            door_positions: vec![],
+            spawn_list: vec![],
+            snapshot_manager: SnapshotManager::new(),
+        }
+    }
+
+    fn take_snapshot(&mut self) {
+        self.snapshot_manager.record_snapshot(self.map.clone());
+    }
+}
+
+/// Lays down the initial shape of a level
+pub trait InitialMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData);
+}
+
+/// Works on a level started by an `InitialMapBuilder`: culls, picks positions, plans spawns
+pub trait MetaMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData);
+}
+
+/// An `InitialMapBuilder` followed by any number of `MetaMapBuilder`s, run in order
+pub struct BuilderChain {
+    starter: Box<dyn InitialMapBuilder>,
+    builders: Vec<Box<dyn MetaMapBuilder>>,
+    build_data: BuildData,
+    final_depth: i32,
+}
+
+impl BuilderChain {
+    #[must_use]
+    pub fn new(
+        width: i32,
+        height: i32,
+        depth: i32,
+        final_depth: i32,
+        starter: impl InitialMapBuilder + 'static,
+    ) -> Self {
+        BuilderChain {
+            starter: Box::new(starter),
+            builders: vec![],
+            build_data: BuildData::new(width, height, depth),
+            final_depth,
+        }
+    }
+
+    #[must_use]
+    pub fn build_data(&self) -> &BuildData {
+        &self.build_data
+    }
+
+    #[must_use]
+    pub fn with(mut self, builder: impl MetaMapBuilder + 'static) -> Self {
+        self.builders.push(Box::new(builder));
+        self
+    }
+
+    /// Start and stairs in rooms, spawns in the other rooms, doors between rooms and corridors
+    #[must_use]
+    fn with_room_stages(self, vaults: PrefabVaults) -> Self {
+        self.with(RoomBasedStartingPosition)
+            .with(RoomBasedStairs)
+            .with(RoomBasedSpawning)
+            .with(vaults)
+            .with(DoorPlacement)
+    }
+
+    /// Random start, stairs as far away as possible, spawns spread over the whole area
+    #[must_use]
+    fn with_cave_stages(self, vaults: PrefabVaults) -> Self {
+        self.with(RandomStartingPosition)
+            .with(CullUnreachable)
+            .with(DistantStairs)
+            .with(AreaBasedSpawning)
+            .with(vaults)
+    }
+}
+
+impl MapBuilder for BuilderChain {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.starter.build_map(rng, &mut self.build_data);
+        for builder in self.builders.iter_mut() {
+            builder.build_map(rng, &mut self.build_data);
+        }
+    }
+
+    fn spawn_entities(&self, commands: &mut CommandBuffer, rng: &mut RandomNumberGenerator) {
+        let depth = self.build_data.map.depth;
+        let spawn_table = Theme::for_depth(depth, self.final_depth).spawn_table(depth);
+        for region in &self.build_data.spawn_regions {
+            spawner::spawn_area(rng, &mut region.clone(), depth, &spawn_table, commands);
+        }
+        for (position, spawner) in &self.build_data.spawn_list {
+            spawner::spawn_at(rng, *spawner, *position, depth, commands);
+        }
+        spawner::spawn_doors(rng, self.build_data.door_positions.clone(), commands);
+    }
+
+    fn get_map(&self) -> Map {
+        self.build_data.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.build_data
+            .starting_position
+            .expect("No stage of the builder chain picked a starting position")
+    }
+
+    fn get_snapshots(&self) -> VecDeque<Map> {
+        self.build_data.snapshot_manager.get_snapshots()
+    }
+}
+
+/// How deep the dungeon goes, and configurations for the builders that take one, so that they can
+/// be tuned outside of the game
+#[derive(Clone, Debug)]
+pub struct MapgenSettings {
+    /// The boss waits on this level. Killing it wins the game.
+    pub final_depth: i32,
+    pub bsp_dungeon: BspConfig,
+    pub bsp_interior: BspConfig,
+    pub cellular_automata: DefaultCellularAutomataConfig,
+}
+
+impl Default for MapgenSettings {
+    fn default() -> Self {
+        MapgenSettings {
+            final_depth: 10,
+            bsp_dungeon: BspConfig::dungeon(),
+            bsp_interior: BspConfig::interior(),
+            cellular_automata: DefaultCellularAutomataConfig::default(),
+        }
+    }
+}
+
+/// Every kind of level `build_valid_map` can come up with
+#[derive(Clone, Copy, Debug, EnumIter, Display)]
+pub enum BuilderKind {
+    BspDungeon,
+    BspInterior,
+    CellularAutomata,
+    DrunkardsWalk,
+    Maze,
+    WfcCave,
+    WfcOutpost,
+    Voronoi,
+    Dla,
+    Simple,
+    BossLair,
+}
+
+impl BuilderKind {
+    /// Some layouts can't meet the thresholds that suit caves and rooms, by design
+    #[must_use]
+    pub fn validator(self, depth: i32, final_depth: i32) -> MapValidator {
+        let validator = MapValidator::for_depth(depth, final_depth);
+        match self {
+            // A maze is nothing but dead ends
+            BuilderKind::Maze => MapValidator {
+                max_dead_end_percent: 100.0,
+                ..validator
+            },
+            // Learning from a fixed prefab picks up its nooks and alcoves
+            BuilderKind::WfcOutpost => MapValidator {
+                max_dead_end_percent: 25.0,
+                ..validator
+            },
+            _ => validator,
+        }
+    }
+}
+
+/// Special depths are drawn by hand
+fn prefab_level(
+    settings: &MapgenSettings,
+    width: i32,
+    height: i32,
+    new_depth: i32,
+    rex_assets: &RexAssets,
+) -> Option<BuilderChain> {
+    let level = rex_assets.levels.get(&new_depth)?;
+    let starter = PrefabLevelBuilder::new(Prefab::from_xp(level));
+    Some(BuilderChain::new(
+        width,
+        height,
+        new_depth,
+        settings.final_depth,
+        starter,
+    ))
+}
+
+fn random_kind(rng: &mut RandomNumberGenerator, depth: i32, final_depth: i32) -> BuilderKind {
+    Theme::for_depth(depth, final_depth)
+        .builders()
+        .roll(rng)
+        .expect("Every theme has builders")
+}
+
+pub fn builder_chain(
+    kind: BuilderKind,
+    settings: &MapgenSettings,
     rng: &mut RandomNumberGenerator,
     width: i32,
     height: i32,
     new_depth: i32,
-) -> Box<dyn MapBuilder> {
-    match rng.roll_dice(1, 6) {
-        1 => Box::new(BspMapBuilder::new(
+    rex_assets: &RexAssets,
+) -> BuilderChain {
+    let vaults = || PrefabVaults::new(rex_assets.vaults.iter().map(Prefab::from_xp).collect());
+    match kind {
+        BuilderKind::BspDungeon => BuilderChain::new(
+            width,
+            height,
+            new_depth,
+            settings.final_depth,
+            BspMapBuilder::new(settings.bsp_dungeon.clone()),
+        )
+        .with(Scatter::new(TileType::Rubble, 2))
+        .with_room_stages(vaults()),
+        BuilderKind::BspInterior => BuilderChain::new(
+            width,
+            height,
+            new_depth,
+            settings.final_depth,
+            BspMapBuilder::new(settings.bsp_interior.clone()),
+        )
+        .with(Scatter::new(TileType::Rubble, 2))
+        .with_room_stages(vaults()),
+        BuilderKind::CellularAutomata => BuilderChain::new(
+            width,
+            height,
+            new_depth,
+            settings.final_depth,
+            CellularAutomataMapBuilder::new(Box::new(settings.cellular_automata.clone())),
+        )
+        .with(NaturalFeatures)
+        .with_cave_stages(vaults()),
+        BuilderKind::DrunkardsWalk => BuilderChain::new(
+            width,
+            height,
+            new_depth,
+            settings.final_depth,
+            DrunkardsWalkMapBuilder,
+        )
+        .with(NaturalFeatures)
+        .with_cave_stages(vaults()),
+        BuilderKind::Maze => BuilderChain::new(
             width,
             height,
             new_depth,
-            BspConfig::dungeon(),
-        )),
-        2 => Box::new(BspMapBuilder::new(
+            settings.final_depth,
+            MazeMapBuilder,
+        )
+        .with_cave_stages(vaults()),
+        // Learn from a cave...
+        BuilderKind::WfcCave => BuilderChain::new(
             width,
             height,
             new_depth,
-            BspConfig::interior(),
-        )),
-        3 => Box::new(CellularAutomataMapBuilder::new(
+            settings.final_depth,
+            CellularAutomataMapBuilder::new(Box::new(settings.cellular_automata.clone())),
+        )
+        .with(WaveFunctionCollapseBuilder)
+        .with_cave_stages(vaults()),
+        // ...or from a hand-authored level
+        BuilderKind::WfcOutpost => BuilderChain::new(
             width,
             height,
             new_depth,
-            Box::new(DefaultCellularAutomataConfig),
-        )),
-        4 => Box::new(DrunkardsWalkMapBuilder::new(width, height, new_depth)),
-        5 => Box::new(MazeMapBuilder::new(width, height, new_depth)),
-        _ => Box::new(SimpleMapBuilder::new(width, height, new_depth)),
+            settings.final_depth,
+            PrefabLevelBuilder::new(Prefab::from_xp(&rex_assets.levels[&ORC_OUTPOST_DEPTH])),
+        )
+        .with(WaveFunctionCollapseBuilder)
+        .with_cave_stages(vaults()),
+        BuilderKind::Voronoi => {
+            let voronoi = match rng.roll_dice(1, 3) {
+                1 => VoronoiMapBuilder::pythagoras(),
+                2 => VoronoiMapBuilder::manhattan(),
+                _ => VoronoiMapBuilder::chebyshev(),
+            };
+            BuilderChain::new(width, height, new_depth, settings.final_depth, voronoi)
+                .with_cave_stages(vaults())
+        }
+        BuilderKind::Dla => {
+            let config = match rng.roll_dice(1, 4) {
+                1 => DlaConfig::walk_inwards(),
+                2 => DlaConfig::walk_outwards(),
+                3 => DlaConfig::central_attractor(),
+                _ => DlaConfig::insectoid(),
+            };
+            BuilderChain::new(
+                width,
+                height,
+                new_depth,
+                settings.final_depth,
+                DlaMapBuilder::new(config),
+            )
+            .with(NaturalFeatures)
+            .with_cave_stages(vaults())
+        }
+        BuilderKind::Simple => BuilderChain::new(
+            width,
+            height,
+            new_depth,
+            settings.final_depth,
+            SimpleMapBuilder,
+        )
+        .with(Scatter::new(TileType::Rubble, 2))
+        .with_room_stages(vaults()),
+        BuilderKind::BossLair => BuilderChain::new(
+            width,
+            height,
+            new_depth,
+            settings.final_depth,
+            CellularAutomataMapBuilder::new(Box::new(settings.cellular_automata.clone())),
+        )
+        .with(NaturalFeatures)
+        .with(RandomStartingPosition)
+        .with(CullUnreachable)
+        .with(AreaBasedSpawning)
+        .with(BossPlacement),
+    }
+}
+
+const MAX_BUILD_ATTEMPTS: i32 = 50;
+
+/// Keeps drawing builders until one of them comes up with a level that passes the
+/// `MapValidator` of its kind. Gives up eventually, rather than hang, and goes with the last
+/// level. Hand-drawn levels are taken as they are.
+pub fn build_valid_map(
+    settings: &MapgenSettings,
+    rng: &mut RandomNumberGenerator,
+    width: i32,
+    height: i32,
+    new_depth: i32,
+    rex_assets: &RexAssets,
+) -> Box<dyn MapBuilder> {
+    if let Some(mut builder) = prefab_level(settings, width, height, new_depth, rex_assets) {
+        builder.build_map(rng);
+        return Box::new(builder);
+    }
+
+    let mut rejections = vec![];
+    for attempt in 1..=MAX_BUILD_ATTEMPTS {
+        let kind = random_kind(rng, new_depth, settings.final_depth);
+        let mut builder = builder_chain(kind, settings, rng, width, height, new_depth, rex_assets);
+        builder.build_map(rng);
+
+        match kind
+            .validator(new_depth, settings.final_depth)
+            .validate(&builder.get_map(), builder.get_starting_position())
+        {
+            Ok(()) => {
+                // Rejections are business as usual, only worth a look when tuning builders
+                if cfg!(feature = "visualize-mapgen") && !rejections.is_empty() {
+                    println!(
+                        "Depth {} built after rejecting {} maps: {}",
+                        new_depth,
+                        attempt - 1,
+                        summarize(&rejections)
+                    );
+                }
+                return Box::new(builder);
+            }
+            Err(mut attempt_rejections) if attempt < MAX_BUILD_ATTEMPTS => {
+                rejections.append(&mut attempt_rejections)
+            }
+            Err(attempt_rejections) => {
+                if cfg!(feature = "visualize-mapgen") {
+                    println!(
+                        "Keeping a {} map for depth {} despite {}, after rejecting {} maps: {}",
+                        kind,
+                        new_depth,
+                        attempt_rejections.iter().join(", "),
+                        attempt - 1,
+                        summarize(&rejections)
+                    );
+                }
+                return Box::new(builder);
+            }
+        }
+    }
+    unreachable!("The last attempt is always kept")
+}
+
+/// How often each reason came up, like `3x too many dead ends, 1x no stairs`
+fn summarize(rejections: &[Rejection]) -> String {
+    rejections
+        .iter()
+        .map(Rejection::reason)
+        .counts()
+        .into_iter()
+        .sorted()
+        .map(|(reason, count)| format!("{}x {}", count, reason))
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::common::connected_region;
+    use crate::mapgen::validation::Rejection;
+    use crate::mapgen::*;
+
+    #[test]
+    fn test_builder_chains_connect_start_and_stairs() {
+        let rex_assets = RexAssets::new();
+        for seed in 0..30 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let settings = MapgenSettings::default();
+            let depth = 1 + seed as i32 % (settings.final_depth - 1);
+            let mut builder =
+                prefab_level(&settings, 80, 43, depth, &rex_assets).unwrap_or_else(|| {
+                    let kind = random_kind(&mut rng, depth, settings.final_depth);
+                    builder_chain(kind, &settings, &mut rng, 80, 43, depth, &rex_assets)
+                });
+            builder.build_map(&mut rng);
+
+            let map = builder.get_map();
+            let reachable = connected_region(&builder.get_starting_position(), &map);
+            assert!(
+                reachable.iter().any(|p| map[p] == TileType::DownStairs),
+                "No stairs reachable with seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_mazes_are_not_judged_by_dead_ends() {
+        let rex_assets = RexAssets::new();
+        for seed in 0..10 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = builder_chain(
+                BuilderKind::Maze,
+                &MapgenSettings::default(),
+                &mut rng,
+                80,
+                43,
+                2,
+                &rex_assets,
+            );
+            builder.build_map(&mut rng);
+
+            let map = builder.get_map();
+            let start = builder.get_starting_position();
+            if let Err(rejections) = BuilderKind::Maze
+                .validator(2, MapgenSettings::default().final_depth)
+                .validate(&map, start)
+            {
+                assert!(!rejections
+                    .iter()
+                    .any(|rejection| matches!(rejection, Rejection::TooManyDeadEnds { .. })));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejections_are_summarized() {
+        let rejections = vec![
+            Rejection::TooManyDeadEnds { percent: 12.0 },
+            Rejection::NoStairs,
+            Rejection::TooManyDeadEnds { percent: 30.0 },
+        ];
+        assert_eq!(
+            summarize(&rejections),
+            "1x no stairs, 2x too many dead ends"
+        );
+    }
+
+    #[test]
+    fn test_boss_is_reachable_on_the_final_level() {
+        let rex_assets = RexAssets::new();
+        for seed in 0..10 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut builder = builder_chain(
+                BuilderKind::BossLair,
+                &MapgenSettings::default(),
+                &mut rng,
+                80,
+                43,
+                MapgenSettings::default().final_depth,
+                &rex_assets,
+            );
+            builder.build_map(&mut rng);
+
+            let map = builder.get_map();
+            let reachable = connected_region(&builder.get_starting_position(), &map);
+            let (boss_position, _) = builder.build_data.spawn_list.last().unwrap();
+            assert!(reachable.contains(boss_position));
+            assert!(!reachable.iter().any(|p| map[p] == TileType::DownStairs));
+        }
     }
 }
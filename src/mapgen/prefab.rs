@@ -0,0 +1,226 @@
+use crate::systems::prelude::*;
+use rand::seq::SliceRandom;
+
+use super::{
+    spawner::{self, Spawner},
+    BuildData, InitialMapBuilder, MetaMapBuilder,
+};
+
+/// A hand-authored piece of map, drawn in REX Paint.
+///
+/// Tiles are drawn with the same glyphs the game uses for them (lava looks just like deep water,
+/// so it can't be drawn). Spawns are drawn with the glyph of what they spawn, on top of floor.
+/// `@` marks where the player starts, blank cells leave the map as it was.
+#[derive(Clone)]
+pub struct Prefab {
+    width: i32,
+    height: i32,
+    tiles: Vec<Option<TileType>>,
+    spawns: Vec<(Vector, Spawner)>,
+    start: Option<Vector>,
+}
+
+fn glyph_tile(glyph: FontCharType) -> Option<TileType> {
+    [
+        TileType::Wall,
+        TileType::Floor,
+        TileType::DownStairs,
+        TileType::ShallowWater,
+        TileType::DeepWater,
+        TileType::Bridge,
+        TileType::Rubble,
+        TileType::Grass,
+        TileType::Chasm,
+    ]
+    .iter()
+    .copied()
+    .find(|tile| tile.glyph() == glyph)
+}
+
+fn glyph_spawner(glyph: FontCharType) -> Option<Spawner> {
+    let spawner: Spawner = match std::char::from_u32(u32::from(glyph))? {
+        'g' => spawner::goblin,
+        'o' => spawner::orc,
+        '!' => spawner::health_potion,
+        '?' => spawner::magic_missile_scroll,
+        ')' => spawner::dagger,
+        '[' => spawner::shield,
+        '%' => spawner::ration,
+        '^' => spawner::bear_trap,
+        '+' => spawner::door,
+        _ => return None,
+    };
+    Some(spawner)
+}
+
+impl Prefab {
+    #[must_use]
+    pub fn from_xp(xp: &XpFile) -> Self {
+        let layer = &xp.layers[0];
+        let mut prefab = Prefab {
+            width: layer.width as i32,
+            height: layer.height as i32,
+            tiles: vec![],
+            spawns: vec![],
+            start: None,
+        };
+
+        for y in 0..layer.height {
+            for x in 0..layer.width {
+                let glyph = layer.get(x, y).unwrap().ch as FontCharType;
+                let offset = Vector::new(x as i32, y as i32);
+                let tile = if let Some(spawner) = glyph_spawner(glyph) {
+                    prefab.spawns.push((offset, spawner));
+                    Some(TileType::Floor)
+                } else if glyph == to_cp437('@') {
+                    prefab.start = Some(offset);
+                    Some(TileType::Floor)
+                } else {
+                    glyph_tile(glyph)
+                };
+                prefab.tiles.push(tile);
+            }
+        }
+
+        prefab
+    }
+
+    /// The prefab and a ring around it must all be plain floor. The ring keeps everything around
+    /// the prefab connected, whatever the prefab itself looks like.
+    fn fits(&self, origin: Position, build_data: &BuildData) -> bool {
+        (-1..=self.height).all(|dy| {
+            (-1..=self.width).all(|dx| {
+                let position = origin + Vector::new(dx, dy);
+                build_data.map.get(position) == Some(TileType::Floor)
+                    && build_data.starting_position != Some(position)
+            })
+        })
+    }
+
+    fn stamp(&self, origin: Position, build_data: &mut BuildData) {
+        for dy in 0..self.height {
+            for dx in 0..self.width {
+                let position = origin + Vector::new(dx, dy);
+                if let Some(tile) = self.tiles[(dy * self.width + dx) as usize] {
+                    if build_data.map.contains(position) {
+                        build_data.map[&position] = tile;
+                    }
+                }
+            }
+        }
+
+        // Whatever was planned inside the prefab gives way to its own contents
+        let footprint = Rect::with_size(origin.x, origin.y, self.width, self.height);
+        for region in build_data.spawn_regions.iter_mut() {
+            region.retain(|position| !footprint.point_in_rect(**position));
+        }
+        build_data
+            .door_positions
+            .retain(|position| !footprint.point_in_rect(**position));
+        for (offset, spawner) in &self.spawns {
+            build_data.spawn_list.push((origin + *offset, *spawner));
+        }
+    }
+}
+
+/// Stamps one of the vaults into the level, somewhere it fits. Half the levels get one.
+pub struct PrefabVaults {
+    vaults: Vec<Prefab>,
+}
+
+impl PrefabVaults {
+    #[must_use]
+    pub fn new(vaults: Vec<Prefab>) -> Self {
+        PrefabVaults { vaults }
+    }
+}
+
+impl MetaMapBuilder for PrefabVaults {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        if rng.roll_dice(1, 2) == 1 {
+            return;
+        }
+
+        self.vaults.shuffle(rng.get_rng());
+        for vault in &self.vaults {
+            let mut origins: Vec<Position> = (1..build_data.map.height - vault.height)
+                .flat_map(|y| (1..build_data.map.width - vault.width).map(move |x| (x, y)))
+                .map(|(x, y)| Position::new(x, y))
+                .collect();
+            origins.shuffle(rng.get_rng());
+
+            let origin = origins
+                .into_iter()
+                .find(|origin| vault.fits(*origin, build_data));
+            if let Some(origin) = origin {
+                vault.stamp(origin, build_data);
+                build_data.take_snapshot();
+                return;
+            }
+        }
+    }
+}
+
+/// Lays out the whole level from a single prefab, for special depths
+pub struct PrefabLevelBuilder {
+    prefab: Prefab,
+}
+
+impl PrefabLevelBuilder {
+    #[must_use]
+    pub fn new(prefab: Prefab) -> Self {
+        PrefabLevelBuilder { prefab }
+    }
+}
+
+impl InitialMapBuilder for PrefabLevelBuilder {
+    fn build_map(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let origin = Position::new(0, 0);
+        self.prefab.stamp(origin, build_data);
+        build_data.starting_position = self.prefab.start.map(|offset| origin + offset);
+        build_data.take_snapshot();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::common::*;
+    use crate::mapgen::prefab::*;
+
+    #[test]
+    fn test_vaults_keep_map_connected() {
+        let rex_assets = RexAssets::new();
+        for xp in &rex_assets.vaults {
+            let mut build_data = BuildData::new(20, 20, 1);
+            apply_room_to_map(&Rect::with_exact(1, 1, 19, 19), &mut build_data.map);
+
+            let vault = Prefab::from_xp(xp);
+            let origin = Position::new(5, 5);
+            assert!(vault.fits(origin, &build_data));
+            vault.stamp(origin, &mut build_data);
+
+            let walkable = walkable_positions(&build_data.map);
+            assert_eq!(
+                connected_region(&walkable[0], &build_data.map).len(),
+                walkable.len()
+            );
+            assert_eq!(build_data.spawn_list.len(), vault.spawns.len());
+        }
+    }
+
+    #[test]
+    fn test_special_levels_have_start_and_stairs() {
+        let rex_assets = RexAssets::new();
+        for xp in rex_assets.levels.values() {
+            let mut build_data = BuildData::new(80, 43, ORC_OUTPOST_DEPTH);
+            let mut rng = RandomNumberGenerator::seeded(1);
+            PrefabLevelBuilder::new(Prefab::from_xp(xp)).build_map(&mut rng, &mut build_data);
+
+            let start = build_data.starting_position.unwrap();
+            let reachable = connected_region(&start, &build_data.map);
+            assert!(reachable
+                .iter()
+                .any(|p| build_data.map[p] == TileType::DownStairs));
+        }
+    }
+}
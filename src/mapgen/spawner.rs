@@ -1,11 +1,12 @@
 use crate::systems::prelude::*;
 
 use rand::prelude::SliceRandom;
-use std::cmp::max;
+use std::cmp::{max, min};
 
+use crate::mapgen::common::walkable_positions;
 use crate::util::random_table::RandomTable;
 
-type Spawner = fn(&mut CommandBuffer) -> Entity;
+pub type Spawner = fn(&mut CommandBuffer) -> Entity;
 
 pub fn player(world: &SubWorld, position: Position, commands: &mut CommandBuffer) {
     if let Some(player_entity) = world.maybe_player_entity() {
@@ -20,6 +21,7 @@ pub fn player(world: &SubWorld, position: Position, commands: &mut CommandBuffer
             },
             Player,
             Name::from("Player".to_string()),
+            Description::from("description.player".to_string()),
             Viewshed::new(8),
             BlocksTile::new(),
             CombatStats {
@@ -29,6 +31,7 @@ pub fn player(world: &SubWorld, position: Position, commands: &mut CommandBuffer
                 power: 5,
             },
             HungerClock::default(),
+            Gold::default(),
         ));
         commands.add_component(player_entity, SerializeMe);
 
@@ -49,6 +52,8 @@ fn add_wizard_items(commands: &mut CommandBuffer, player_entity: Entity) {
         shield(commands),
         ration(commands),
         magic_mapping_scroll(commands),
+        identify_scroll(commands),
+        remove_curse_scroll(commands),
     ];
     for wizard_item in wizard_items {
         commands.add_component(
@@ -60,54 +65,119 @@ fn add_wizard_items(commands: &mut CommandBuffer, player_entity: Entity) {
     }
 }
 
-pub fn spawn_room(
+/// Fill some of `area` with rolls on the level's spawn table. Deeper levels are more crowded.
+pub fn spawn_area(
     rng: &mut RandomNumberGenerator,
-    room: &Rect,
+    area: &mut Vec<Position>,
     depth: i32,
+    spawn_table: &RandomTable<Spawner>,
     commands: &mut CommandBuffer,
 ) {
-    spawn_area(
-        rng,
-        &mut room
-            .point_set()
-            .iter()
-            .map(|&p| Position::from(p))
-            .collect(),
-        depth,
-        commands,
-    );
+    let spawnable_count = max(0, rng.range(-2, 4 + depth)) as usize;
+    for position in area.partial_shuffle(rng.get_rng(), spawnable_count).0 {
+        if let Some(spawner) = spawn_table.roll(rng) {
+            spawn_at(rng, spawner, *position, depth, commands);
+        }
+    }
 }
 
-pub fn spawn_area(
+/// Spawn a specific thing at a specific place, like the contents of prefabs
+pub fn spawn_at(
     rng: &mut RandomNumberGenerator,
-    area: &mut Vec<Position>,
+    spawner: Spawner,
+    position: Position,
     depth: i32,
     commands: &mut CommandBuffer,
 ) {
-    let room_table = RandomTable::<Spawner>::new()
-        .add(goblin, 10)
-        .add(orc, 1 + depth)
-        .add(health_potion, 7)
-        .add(fireball_scroll, 2 + depth)
-        .add(confusion_scroll, 2 + depth)
+    let new_entity = spawner(commands);
+    commands.add_component(new_entity, position);
+    enchant(rng, new_entity, depth, commands);
+}
+
+/// Shopkeepers set up shop on some levels below the first one
+pub fn maybe_vendor(
+    rng: &mut RandomNumberGenerator,
+    map: &Map,
+    starting_position: Position,
+    commands: &mut CommandBuffer,
+) {
+    if map.depth < 2 || rng.roll_dice(1, 3) != 1 {
+        return;
+    }
+    // Not on the stairs either, where the vendor would be in the way
+    let candidates: Vec<Position> = walkable_positions(map)
+        .into_iter()
+        .filter(|p| *p != starting_position && map[p] == TileType::Floor)
+        .collect();
+    let position = match rng.random_slice_entry(&candidates) {
+        Some(&position) => position,
+        None => return,
+    };
+    let vendor = vendor(commands);
+    commands.add_component(vendor, position);
+
+    let stock_table = RandomTable::<Spawner>::new()
+        .add(health_potion, 10)
         .add(magic_missile_scroll, 4)
+        .add(fireball_scroll, 2 + map.depth)
+        .add(confusion_scroll, 2 + map.depth)
+        .add(magic_mapping_scroll, 3)
+        .add(identify_scroll, 5)
+        .add(remove_curse_scroll, 3)
         .add(dagger, 3)
         .add(shield, 3)
-        .add(long_sword, depth - 1)
-        .add(tower_shield, depth - 1)
-        .add(ration, 10)
-        .add(magic_mapping_scroll, 2)
-        .add(bear_trap, 2);
-    let spawnable_count = max(0, rng.range(-2, 4 + depth)) as usize;
-    for position in area.partial_shuffle(rng.get_rng(), spawnable_count).0 {
-        if let Some(spawner) = room_table.roll(rng) {
-            let new_entity = spawner(commands);
-            commands.add_component(new_entity, *position);
+        .add(long_sword, map.depth)
+        .add(tower_shield, map.depth)
+        .add(ration, 8);
+    for _ in 0..rng.range(4, 8) {
+        if let Some(spawner) = stock_table.roll(rng) {
+            let item = spawner(commands);
+            commands.add_component(item, InBackpack::new(vendor));
+            enchant(rng, item, map.depth, commands);
         }
     }
 }
 
-pub fn monster<S: ToString>(commands: &mut CommandBuffer, letter: char, name: S) -> Entity {
+/// Deeper levels hold both stronger magic and nastier curses.
+/// Only applies to equipment, everything else is left untouched.
+fn enchant(
+    rng: &mut RandomNumberGenerator,
+    entity: Entity,
+    depth: i32,
+    commands: &mut CommandBuffer,
+) {
+    let curse_chance = min(5 + depth * 3, 35);
+    let enchant_chance = min(10 + depth * 2, 40);
+
+    let roll = rng.roll_dice(1, 100);
+    let (level, cursed) = if roll <= curse_chance {
+        (-rng.roll_dice(1, 2), true)
+    } else if roll <= curse_chance + enchant_chance {
+        (rng.roll_dice(1, 1 + depth / 3), false)
+    } else {
+        return;
+    };
+
+    // Spawners only hand out a fresh entity, so we need to wait for it to exist to tell
+    // whether it's equipment
+    commands.exec_mut(move |w| {
+        let mut entry = w.entry(entity).unwrap();
+        if !entry.archetype().layout().has_component::<Equippable>() {
+            return;
+        }
+        entry.add_component(Enchantment::new(level));
+        if cursed {
+            entry.add_component(Cursed);
+        }
+    });
+}
+
+pub fn monster<S: ToString>(
+    commands: &mut CommandBuffer,
+    letter: char,
+    name: S,
+    description: S,
+) -> Entity {
     commands.push((
         Renderable {
             glyph: to_cp437(letter),
@@ -117,6 +187,7 @@ pub fn monster<S: ToString>(commands: &mut CommandBuffer, letter: char, name: S)
         Viewshed::new(8),
         Monster,
         Name::from(name.to_string()),
+        Description::from(description.to_string()),
         BlocksTile::new(),
         CombatStats {
             max_hp: 16,
@@ -128,12 +199,42 @@ pub fn monster<S: ToString>(commands: &mut CommandBuffer, letter: char, name: S)
     ))
 }
 
+pub fn vendor(commands: &mut CommandBuffer) -> Entity {
+    commands.push((
+        Renderable {
+            glyph: to_cp437('@'),
+            color: ColorPair::new(GREEN, BLACK),
+            render_order: RenderOrder::Monsters,
+        },
+        Vendor,
+        Name::from("Shopkeeper".to_string()),
+        Description::from("description.shopkeeper".to_string()),
+        BlocksTile::new(),
+        SerializeMe,
+    ))
+}
+
 pub fn orc(commands: &mut CommandBuffer) -> Entity {
-    monster(commands, 'o', "Orc")
+    monster(commands, 'o', "Orc", "description.orc")
 }
 
 pub fn goblin(commands: &mut CommandBuffer) -> Entity {
-    monster(commands, 'g', "Goblin")
+    monster(commands, 'g', "Goblin", "description.goblin")
+}
+
+pub fn orc_warlord(commands: &mut CommandBuffer) -> Entity {
+    let boss = monster(commands, 'O', "Orc Warlord", "description.orc_warlord");
+    commands.add_component(
+        boss,
+        CombatStats {
+            max_hp: 60,
+            hp: 60,
+            defense: 4,
+            power: 9,
+        },
+    );
+    commands.add_component(boss, Boss);
+    boss
 }
 
 pub fn health_potion(commands: &mut CommandBuffer) -> Entity {
@@ -144,9 +245,14 @@ pub fn health_potion(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Health Potion".to_string()),
+        Description::from("description.health_potion".to_string()),
         Item,
+        Weight::new(0.5),
+        Value::new(25),
+        Identifiable::new(Appearance::Potion),
         ProvidesHealing { heal_amount: 8 },
         Consumable,
+        Quantity::default(),
         SerializeMe,
     ))
 }
@@ -154,13 +260,18 @@ pub fn health_potion(commands: &mut CommandBuffer) -> Entity {
 pub fn magic_missile_scroll(commands: &mut CommandBuffer) -> Entity {
     commands.push((
         Renderable {
-            glyph: to_cp437(')'),
-            color: ColorPair::new(CYAN, BLACK),
+            glyph: to_cp437('?'),
+            color: ColorPair::new(WHEAT, BLACK),
             render_order: RenderOrder::Items,
         },
         Name::from("Magic Missile Scroll".to_string()),
+        Description::from("description.magic_missile_scroll".to_string()),
         Item,
+        Weight::new(0.5),
+        Value::new(30),
+        Identifiable::new(Appearance::Scroll),
         Consumable,
+        Quantity::default(),
         Ranged { range: 6 },
         InflictsDamage { damage: 8 },
         SerializeMe,
@@ -170,13 +281,18 @@ pub fn magic_missile_scroll(commands: &mut CommandBuffer) -> Entity {
 pub fn fireball_scroll(commands: &mut CommandBuffer) -> Entity {
     commands.push((
         Renderable {
-            glyph: to_cp437(')'),
-            color: ColorPair::new(ORANGE, BLACK),
+            glyph: to_cp437('?'),
+            color: ColorPair::new(WHEAT, BLACK),
             render_order: RenderOrder::Items,
         },
         Name::from("Fireball Scroll".to_string()),
+        Description::from("description.fireball_scroll".to_string()),
         Item,
+        Weight::new(0.5),
+        Value::new(60),
+        Identifiable::new(Appearance::Scroll),
         Consumable,
+        Quantity::default(),
         Ranged { range: 6 },
         InflictsDamage { damage: 20 },
         AreaOfEffect { radius: 3 },
@@ -187,13 +303,18 @@ pub fn fireball_scroll(commands: &mut CommandBuffer) -> Entity {
 pub fn confusion_scroll(commands: &mut CommandBuffer) -> Entity {
     commands.push((
         Renderable {
-            glyph: to_cp437(')'),
-            color: ColorPair::new(PINK, BLACK),
+            glyph: to_cp437('?'),
+            color: ColorPair::new(WHEAT, BLACK),
             render_order: RenderOrder::Items,
         },
         Name::from("Confusion Scroll".to_string()),
+        Description::from("description.confusion_scroll".to_string()),
         Item,
+        Weight::new(0.5),
+        Value::new(40),
+        Identifiable::new(Appearance::Scroll),
         Consumable,
+        Quantity::default(),
         Ranged { range: 6 },
         Confusion { turns: 4 },
         SerializeMe,
@@ -203,18 +324,63 @@ pub fn confusion_scroll(commands: &mut CommandBuffer) -> Entity {
 pub fn magic_mapping_scroll(commands: &mut CommandBuffer) -> Entity {
     commands.push((
         Renderable {
-            glyph: to_cp437(')'),
-            color: ColorPair::new(RGB::named(CYAN3), BLACK),
+            glyph: to_cp437('?'),
+            color: ColorPair::new(WHEAT, BLACK),
             render_order: RenderOrder::Items,
         },
         Name::from("Scroll of Magic Mapping".to_string()),
+        Description::from("description.scroll_of_magic_mapping".to_string()),
         Item,
+        Weight::new(0.5),
+        Value::new(50),
+        Identifiable::new(Appearance::Scroll),
         Consumable,
+        Quantity::default(),
         MagicMapper,
         SerializeMe,
     ))
 }
 
+pub fn identify_scroll(commands: &mut CommandBuffer) -> Entity {
+    commands.push((
+        Renderable {
+            glyph: to_cp437('?'),
+            color: ColorPair::new(WHEAT, BLACK),
+            render_order: RenderOrder::Items,
+        },
+        Name::from("Scroll of Identify".to_string()),
+        Description::from("description.scroll_of_identify".to_string()),
+        Item,
+        Weight::new(0.5),
+        Value::new(30),
+        Identifiable::new(Appearance::Scroll),
+        Consumable,
+        Quantity::default(),
+        Identifies,
+        SerializeMe,
+    ))
+}
+
+pub fn remove_curse_scroll(commands: &mut CommandBuffer) -> Entity {
+    commands.push((
+        Renderable {
+            glyph: to_cp437('?'),
+            color: ColorPair::new(WHEAT, BLACK),
+            render_order: RenderOrder::Items,
+        },
+        Name::from("Scroll of Remove Curse".to_string()),
+        Description::from("description.scroll_of_remove_curse".to_string()),
+        Item,
+        Weight::new(0.5),
+        Value::new(40),
+        Identifiable::new(Appearance::Scroll),
+        Consumable,
+        Quantity::default(),
+        RemovesCurse,
+        SerializeMe,
+    ))
+}
+
 pub fn dagger(commands: &mut CommandBuffer) -> Entity {
     commands.push((
         Renderable {
@@ -223,7 +389,10 @@ pub fn dagger(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Dagger".to_string()),
+        Description::from("description.dagger".to_string()),
         Item,
+        Weight::new(1.0),
+        Value::new(20),
         Equippable::new(EquipmentSlot::Melee),
         MeleePowerBonus::new(2),
         SerializeMe,
@@ -238,7 +407,10 @@ pub fn long_sword(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Long Sword".to_string()),
+        Description::from("description.long_sword".to_string()),
         Item,
+        Weight::new(3.0),
+        Value::new(60),
         Equippable::new(EquipmentSlot::Melee),
         MeleePowerBonus::new(4),
         SerializeMe,
@@ -253,7 +425,10 @@ pub fn shield(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Shield".to_string()),
+        Description::from("description.shield".to_string()),
         Item,
+        Weight::new(5.0),
+        Value::new(30),
         Equippable::new(EquipmentSlot::Shield),
         DefenseBonus::new(1),
         SerializeMe,
@@ -268,7 +443,10 @@ pub fn tower_shield(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Tower Shield".to_string()),
+        Description::from("description.tower_shield".to_string()),
         Item,
+        Weight::new(10.0),
+        Value::new(80),
         Equippable::new(EquipmentSlot::Shield),
         DefenseBonus::new(3),
         SerializeMe,
@@ -283,9 +461,13 @@ pub fn ration(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Rations".to_string()),
+        Description::from("description.rations".to_string()),
         Item,
+        Weight::new(0.5),
+        Value::new(10),
         ProvidesFood,
         Consumable,
+        Quantity::default(),
         SerializeMe,
     ))
 }
@@ -298,6 +480,7 @@ pub fn bear_trap(commands: &mut CommandBuffer) -> Entity {
             render_order: RenderOrder::Items,
         },
         Name::from("Bear Trap".to_string()),
+        Description::from("description.bear_trap".to_string()),
         Hidden,
         EntryTrigger,
         InflictsDamage { damage: 6 },
@@ -305,3 +488,71 @@ pub fn bear_trap(commands: &mut CommandBuffer) -> Entity {
         SerializeMe,
     ))
 }
+
+pub fn door(commands: &mut CommandBuffer) -> Entity {
+    let door = Door::new(false);
+    commands.push((
+        Renderable {
+            glyph: door.glyph(),
+            color: ColorPair::new(CHOCOLATE, BLACK),
+            render_order: RenderOrder::Items,
+        },
+        Name::from("Door".to_string()),
+        Description::from("description.door".to_string()),
+        door,
+        SerializeMe,
+    ))
+}
+
+/// Not every doorway gets a door
+pub fn spawn_doors(
+    rng: &mut RandomNumberGenerator,
+    positions: Vec<Position>,
+    commands: &mut CommandBuffer,
+) {
+    for position in positions {
+        if rng.roll_dice(1, 3) != 1 {
+            let door = door(commands);
+            commands.add_component(door, position);
+        }
+    }
+}
+
+pub fn gold_pile(commands: &mut CommandBuffer, amount: i32) -> Entity {
+    commands.push((
+        Renderable {
+            glyph: to_cp437('$'),
+            color: ColorPair::new(GOLD, BLACK),
+            render_order: RenderOrder::Items,
+        },
+        Name::from("Gold".to_string()),
+        Description::from("description.gold".to_string()),
+        Item,
+        Gold::new(amount),
+        SerializeMe,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use legion::{systems::CommandBuffer, Resources, World};
+
+    use crate::mapgen::spawner::*;
+
+    #[test]
+    fn test_no_vendor_without_room_for_one() {
+        let start = Position::new(2, 2);
+        let mut map = Map::new(5, 5, 4);
+        map[&start] = TileType::Floor;
+        map[&Position::new(3, 2)] = TileType::DownStairs;
+
+        for seed in 0..10 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mut world = World::default();
+            let mut commands = CommandBuffer::new(&world);
+            maybe_vendor(&mut rng, &map, start, &mut commands);
+            commands.flush(&mut world, &mut Resources::default());
+            assert!(world.is_empty());
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::systems::prelude::*;
+use crate::util::random_table::RandomTable;
+
+use super::spawner::{self, Spawner};
+use super::BuilderKind;
+
+/// What the levels look like and what lives in them, by depth
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Theme {
+    Caves,
+    Fortress,
+    Ruins,
+    Lair,
+}
+
+/// Colors for the tiles that make up most of a level
+pub struct Palette {
+    wall: RGB,
+    floor: RGB,
+}
+
+impl Palette {
+    #[must_use]
+    pub fn color(&self, tile: TileType) -> RGB {
+        match tile {
+            TileType::Wall => self.wall,
+            TileType::Floor => self.floor,
+            _ => tile.color(),
+        }
+    }
+}
+
+impl Theme {
+    #[must_use]
+    pub fn for_depth(depth: i32, final_depth: i32) -> Theme {
+        if depth >= final_depth {
+            Theme::Lair
+        } else if depth <= 3 {
+            Theme::Caves
+        } else if depth <= 6 {
+            Theme::Fortress
+        } else {
+            Theme::Ruins
+        }
+    }
+
+    /// Mazes are a rare treat on any level above the lair
+    #[must_use]
+    pub fn builders(self) -> RandomTable<BuilderKind> {
+        match self {
+            Theme::Caves => RandomTable::new()
+                .add(BuilderKind::CellularAutomata, 4)
+                .add(BuilderKind::DrunkardsWalk, 3)
+                .add(BuilderKind::Dla, 2)
+                .add(BuilderKind::WfcCave, 1)
+                .add(BuilderKind::Maze, 1),
+            Theme::Fortress => RandomTable::new()
+                .add(BuilderKind::BspDungeon, 3)
+                .add(BuilderKind::BspInterior, 2)
+                .add(BuilderKind::Simple, 3)
+                .add(BuilderKind::WfcOutpost, 1)
+                .add(BuilderKind::Maze, 1),
+            Theme::Ruins => RandomTable::new()
+                .add(BuilderKind::Voronoi, 3)
+                .add(BuilderKind::BspDungeon, 2)
+                .add(BuilderKind::WfcCave, 1)
+                .add(BuilderKind::WfcOutpost, 1)
+                .add(BuilderKind::Dla, 1)
+                .add(BuilderKind::Maze, 1),
+            Theme::Lair => RandomTable::new().add(BuilderKind::BossLair, 1),
+        }
+    }
+
+    #[must_use]
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Caves => Palette {
+                wall: RGB::named(GREEN),
+                floor: RGB::named(GRAY50),
+            },
+            Theme::Fortress => Palette {
+                wall: RGB::from_f32(0.7, 0.7, 0.75),
+                floor: RGB::from_f32(0.45, 0.45, 0.5),
+            },
+            Theme::Ruins => Palette {
+                wall: RGB::from_f32(0.75, 0.6, 0.35),
+                floor: RGB::from_f32(0.5, 0.45, 0.35),
+            },
+            Theme::Lair => Palette {
+                wall: RGB::from_f32(0.65, 0.1, 0.1),
+                floor: RGB::from_f32(0.45, 0.3, 0.3),
+            },
+        }
+    }
+
+    /// Goblins give way to orcs, and better loot, the deeper you go
+    #[must_use]
+    pub fn spawn_table(self, depth: i32) -> RandomTable<Spawner> {
+        match self {
+            Theme::Caves => RandomTable::<Spawner>::new()
+                .add(spawner::goblin, 12)
+                .add(spawner::orc, depth)
+                .add(spawner::health_potion, 7)
+                .add(spawner::fireball_scroll, 1 + depth)
+                .add(spawner::confusion_scroll, 2 + depth)
+                .add(spawner::magic_missile_scroll, 4)
+                .add(spawner::dagger, 3)
+                .add(spawner::shield, 3)
+                .add(spawner::ration, 10)
+                .add(spawner::magic_mapping_scroll, 2)
+                .add(spawner::identify_scroll, 3)
+                .add(spawner::remove_curse_scroll, 1 + depth / 2)
+                .add(spawner::bear_trap, 1),
+            Theme::Fortress => RandomTable::<Spawner>::new()
+                .add(spawner::goblin, 5)
+                .add(spawner::orc, 6 + depth)
+                .add(spawner::health_potion, 7)
+                .add(spawner::fireball_scroll, 2 + depth)
+                .add(spawner::confusion_scroll, 2 + depth)
+                .add(spawner::magic_missile_scroll, 4)
+                .add(spawner::dagger, 2)
+                .add(spawner::shield, 2)
+                .add(spawner::long_sword, depth - 1)
+                .add(spawner::tower_shield, depth - 1)
+                .add(spawner::ration, 8)
+                .add(spawner::magic_mapping_scroll, 2)
+                .add(spawner::identify_scroll, 3)
+                .add(spawner::remove_curse_scroll, 1 + depth / 2)
+                .add(spawner::bear_trap, 4),
+            Theme::Ruins => RandomTable::<Spawner>::new()
+                .add(spawner::goblin, 3)
+                .add(spawner::orc, 8 + depth)
+                .add(spawner::health_potion, 9)
+                .add(spawner::fireball_scroll, 3 + depth)
+                .add(spawner::confusion_scroll, 3 + depth)
+                .add(spawner::magic_missile_scroll, 5)
+                .add(spawner::long_sword, depth)
+                .add(spawner::tower_shield, depth)
+                .add(spawner::ration, 10)
+                .add(spawner::magic_mapping_scroll, 3)
+                .add(spawner::identify_scroll, 4)
+                .add(spawner::remove_curse_scroll, 2 + depth / 2)
+                .add(spawner::bear_trap, 3),
+            Theme::Lair => RandomTable::<Spawner>::new()
+                .add(spawner::goblin, 4)
+                .add(spawner::orc, 10)
+                .add(spawner::health_potion, 10)
+                .add(spawner::ration, 6)
+                .add(spawner::bear_trap, 2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::themes::*;
+    use crate::mapgen::MapgenSettings;
+
+    #[test]
+    fn test_every_depth_has_a_theme() {
+        let final_depth = MapgenSettings::default().final_depth;
+        assert_eq!(Theme::for_depth(1, final_depth), Theme::Caves);
+        assert_eq!(
+            Theme::for_depth(ORC_OUTPOST_DEPTH, final_depth),
+            Theme::Fortress
+        );
+        assert_eq!(Theme::for_depth(final_depth - 1, final_depth), Theme::Ruins);
+        assert_eq!(Theme::for_depth(final_depth, final_depth), Theme::Lair);
+
+        let mut rng = RandomNumberGenerator::seeded(3);
+        for depth in 1..=final_depth {
+            let theme = Theme::for_depth(depth, final_depth);
+            assert!(theme.builders().roll(&mut rng).is_some());
+            assert!(theme.spawn_table(depth).roll(&mut rng).is_some());
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+
+use crate::systems::prelude::*;
+
+use super::common::walkable_positions;
+
+/// Reasons for throwing a generated level away
+#[derive(Debug, PartialEq)]
+pub enum Rejection {
+    TooLittleFloor { percent: f32 },
+    NoStairs,
+    StairsUnreachable,
+    StairsTooClose { distance: f32 },
+    TooManyDeadEnds { percent: f32 },
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::TooLittleFloor { percent } => write!(f, "only {:.1}% floor", percent),
+            Rejection::NoStairs => write!(f, "no stairs"),
+            Rejection::StairsUnreachable => write!(f, "stairs unreachable from the start"),
+            Rejection::StairsTooClose { distance } => {
+                write!(f, "stairs only {:.1} steps from the start", distance)
+            }
+            Rejection::TooManyDeadEnds { percent } => write!(f, "{:.1}% dead ends", percent),
+        }
+    }
+}
+
+impl Rejection {
+    /// What went wrong, without the numbers
+    #[must_use]
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Rejection::TooLittleFloor { .. } => "too little floor",
+            Rejection::NoStairs => "no stairs",
+            Rejection::StairsUnreachable => "stairs unreachable",
+            Rejection::StairsTooClose { .. } => "stairs too close",
+            Rejection::TooManyDeadEnds { .. } => "too many dead ends",
+        }
+    }
+}
+
+/// Quality checks run on the finished level. `BuilderKind::validator` adjusts them per builder.
+pub struct MapValidator {
+    pub min_floor_percent: f32,
+    /// There's no way further down from the final level
+    pub require_stairs: bool,
+    pub min_stairs_distance: f32,
+    pub max_dead_end_percent: f32,
+}
+
+impl Default for MapValidator {
+    fn default() -> Self {
+        MapValidator {
+            min_floor_percent: 15.0,
+            require_stairs: true,
+            min_stairs_distance: 15.0,
+            max_dead_end_percent: 10.0,
+        }
+    }
+}
+
+impl MapValidator {
+    #[must_use]
+    pub fn for_depth(depth: i32, final_depth: i32) -> Self {
+        MapValidator {
+            require_stairs: depth < final_depth,
+            ..MapValidator::default()
+        }
+    }
+
+    pub fn validate(&self, map: &Map, starting_position: Position) -> Result<(), Vec<Rejection>> {
+        let mut rejections = vec![];
+        let walkable = walkable_positions(map);
+
+        let floor_percent = walkable.len() as f32 * 100.0 / (map.width * map.height) as f32;
+        if floor_percent < self.min_floor_percent {
+            rejections.push(Rejection::TooLittleFloor {
+                percent: floor_percent,
+            });
+        }
+
+        let dijkstra_map = DijkstraMap::new(
+            map.width,
+            map.height,
+            &[map.pos_idx(starting_position)],
+            map,
+            std::f32::MAX,
+        );
+        let stairs_distance = walkable
+            .iter()
+            .filter(|position| map[*position] == TileType::DownStairs)
+            .map(|position| dijkstra_map.map[map.pos_idx(*position)])
+            .min_by(|a, b| a.partial_cmp(b).unwrap());
+        match stairs_distance {
+            _ if !self.require_stairs => {}
+            None => rejections.push(Rejection::NoStairs),
+            Some(distance) if distance == std::f32::MAX => {
+                rejections.push(Rejection::StairsUnreachable)
+            }
+            Some(distance) if distance < self.min_stairs_distance => {
+                rejections.push(Rejection::StairsTooClose { distance })
+            }
+            _ => {}
+        }
+
+        let dead_ends = walkable
+            .iter()
+            .filter(|position| Self::is_dead_end(**position, map))
+            .count();
+        let dead_end_percent = dead_ends as f32 * 100.0 / walkable.len().max(1) as f32;
+        if dead_end_percent > self.max_dead_end_percent {
+            rejections.push(Rejection::TooManyDeadEnds {
+                percent: dead_end_percent,
+            });
+        }
+
+        if rejections.is_empty() {
+            Ok(())
+        } else {
+            Err(rejections)
+        }
+    }
+
+    /// Can only be left the way it was entered
+    fn is_dead_end(position: Position, map: &Map) -> bool {
+        let mut neighbors = 0;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if (dx != 0 || dy != 0)
+                    && map
+                        .get(position + Vector::new(dx, dy))
+                        .map_or(false, TileType::is_walkable)
+                {
+                    neighbors += 1;
+                }
+            }
+        }
+        neighbors == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::common::*;
+    use crate::mapgen::validation::*;
+
+    fn corridor() -> Map {
+        let mut map = Map::new(40, 5, 1);
+        apply_horizontal_tunnel(&mut map, 1, 38, 2);
+        map[(38, 2)] = TileType::DownStairs;
+        map
+    }
+
+    #[test]
+    fn test_accepts_long_corridor() {
+        let validator = MapValidator {
+            min_floor_percent: 15.0,
+            require_stairs: true,
+            min_stairs_distance: 30.0,
+            max_dead_end_percent: 10.0,
+        };
+        assert_eq!(validator.validate(&corridor(), Position::new(1, 2)), Ok(()));
+    }
+
+    #[test]
+    fn test_rejection_reasons() {
+        let validator = MapValidator {
+            min_floor_percent: 50.0,
+            require_stairs: true,
+            min_stairs_distance: 30.0,
+            max_dead_end_percent: 1.0,
+        };
+        let mut map = corridor();
+        map[(20, 2)] = TileType::Chasm;
+
+        let rejections = validator.validate(&map, Position::new(1, 2)).unwrap_err();
+        assert_eq!(rejections.len(), 3);
+        assert!(matches!(rejections[0], Rejection::TooLittleFloor { .. }));
+        assert_eq!(rejections[1], Rejection::StairsUnreachable);
+        assert!(matches!(rejections[2], Rejection::TooManyDeadEnds { .. }));
+
+        map[(20, 2)] = TileType::Floor;
+        let rejections = validator.validate(&map, Position::new(30, 2)).unwrap_err();
+        assert!(rejections.contains(&Rejection::StairsTooClose { distance: 8.0 }));
+    }
+}
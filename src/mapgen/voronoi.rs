@@ -0,0 +1,115 @@
+use crate::systems::prelude::*;
+
+use super::{BuildData, InitialMapBuilder};
+
+/// Carves a honeycomb of cells around randomly placed seeds. Every tile belongs to the nearest
+/// seed; tiles on the border between two cells become walls.
+///
+/// Nothing guarantees that all the cells are connected, so follow up with `CullUnreachable`.
+pub struct VoronoiMapBuilder {
+    seed_count: usize,
+    distance_algorithm: DistanceAlg,
+}
+
+impl VoronoiMapBuilder {
+    #[must_use]
+    pub fn new(seed_count: usize, distance_algorithm: DistanceAlg) -> Self {
+        VoronoiMapBuilder {
+            seed_count,
+            distance_algorithm,
+        }
+    }
+
+    #[must_use]
+    pub fn pythagoras() -> Self {
+        Self::new(64, DistanceAlg::Pythagoras)
+    }
+
+    #[must_use]
+    pub fn manhattan() -> Self {
+        Self::new(64, DistanceAlg::Manhattan)
+    }
+
+    #[must_use]
+    pub fn chebyshev() -> Self {
+        Self::new(64, DistanceAlg::Chebyshev)
+    }
+}
+
+impl InitialMapBuilder for VoronoiMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let (width, height) = (build_data.map.width, build_data.map.height);
+
+        let mut seeds: Vec<Point> = vec![];
+        while seeds.len() < self.seed_count {
+            let candidate = Point::new(rng.roll_dice(1, width - 1), rng.roll_dice(1, height - 1));
+            if !seeds.contains(&candidate) {
+                seeds.push(candidate);
+            }
+        }
+
+        let idx = |x: i32, y: i32| (y * width + x) as usize;
+        let mut membership = vec![0; (width * height) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let point = Point::new(x, y);
+                membership[idx(x, y)] = seeds
+                    .iter()
+                    .map(|seed| self.distance_algorithm.distance2d(point, *seed))
+                    .enumerate()
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                    .unwrap()
+                    .0;
+            }
+        }
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let seed = membership[idx(x, y)];
+                let foreign_neighbors = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .iter()
+                    .filter(|(nx, ny)| membership[idx(*nx, *ny)] != seed)
+                    .count();
+                if foreign_neighbors < 2 {
+                    build_data.map[(x, y)] = TileType::Floor;
+                }
+            }
+            if y % 4 == 0 {
+                build_data.take_snapshot();
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::common::*;
+    use crate::mapgen::prefab::PrefabVaults;
+    use crate::mapgen::voronoi::*;
+    use crate::mapgen::{BuilderChain, MapBuilder, MapgenSettings};
+
+    #[test]
+    fn test_voronoi_stairs_reachable() {
+        for (seed, builder) in vec![
+            VoronoiMapBuilder::pythagoras(),
+            VoronoiMapBuilder::manhattan(),
+            VoronoiMapBuilder::chebyshev(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut rng = RandomNumberGenerator::seeded(seed as u64);
+            let final_depth = MapgenSettings::default().final_depth;
+            let mut chain = BuilderChain::new(80, 43, 1, final_depth, builder)
+                .with_cave_stages(PrefabVaults::new(vec![]));
+            chain.build_map(&mut rng);
+
+            let map = chain.get_map();
+            let walkable = walkable_positions(&map);
+            let reachable = connected_region(&chain.get_starting_position(), &map);
+            assert_eq!(reachable.len(), walkable.len());
+            assert!(reachable.iter().any(|p| map[p] == TileType::DownStairs));
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::systems::prelude::*;
+
+use strum::IntoEnumIterator;
+
+use super::{common::*, BuildData, MetaMapBuilder};
+
+const CHUNK_SIZE: i32 = 8;
+const MAX_ATTEMPTS: i32 = 20;
+
+/// A `CHUNK_SIZE` square of tiles lifted from the sample map
+#[derive(PartialEq)]
+struct Pattern {
+    tiles: Vec<TileType>,
+}
+
+impl Pattern {
+    fn from_map(map: &Map, origin: Position) -> Self {
+        let mut tiles = vec![];
+        for y in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let tile = map[&(origin + Vector::new(x, y))];
+                // Stairs are placed later, on the new map
+                tiles.push(if tile == TileType::DownStairs {
+                    TileType::Floor
+                } else {
+                    tile
+                });
+            }
+        }
+        Pattern { tiles }
+    }
+
+    fn tile(&self, x: i32, y: i32) -> TileType {
+        self.tiles[(y * CHUNK_SIZE + x) as usize]
+    }
+
+    fn flipped(&self, horizontal: bool, vertical: bool) -> Self {
+        let mut tiles = vec![];
+        for y in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let source_x = if horizontal { CHUNK_SIZE - 1 - x } else { x };
+                let source_y = if vertical { CHUNK_SIZE - 1 - y } else { y };
+                tiles.push(self.tile(source_x, source_y));
+            }
+        }
+        Pattern { tiles }
+    }
+
+    /// Which tiles along the edge facing `heading` can be walked on
+    fn exits(&self, heading: Heading) -> Vec<bool> {
+        (0..CHUNK_SIZE)
+            .map(|i| {
+                let (x, y) = match heading {
+                    Heading::North => (i, 0),
+                    Heading::South => (i, CHUNK_SIZE - 1),
+                    Heading::West => (0, i),
+                    Heading::East => (CHUNK_SIZE - 1, i),
+                };
+                self.tile(x, y).is_walkable()
+            })
+            .collect()
+    }
+}
+
+/// Can `b` sit next to `a`, in the direction `heading`?
+/// Edges fit if they're both sealed, or if their exits line up exactly.
+fn compatible(a: &Pattern, b: &Pattern, heading: Heading) -> bool {
+    let a_exits = a.exits(heading);
+    let b_exits = b.exits(heading.rotate_cw().rotate_cw());
+    let sealed = |exits: &[bool]| exits.iter().all(|exit| !exit);
+    (sealed(&a_exits) && sealed(&b_exits)) || a_exits == b_exits
+}
+
+/// Learns `CHUNK_SIZE` patterns from whatever the chain built so far, and synthesizes a brand
+/// new level out of them. Anything the earlier stages planned is thrown away.
+pub struct WaveFunctionCollapseBuilder;
+
+impl WaveFunctionCollapseBuilder {
+    fn learn_patterns(sample: &Map) -> Vec<Pattern> {
+        let mut patterns: Vec<Pattern> = vec![];
+        for chunk_y in 0..sample.height / CHUNK_SIZE {
+            for chunk_x in 0..sample.width / CHUNK_SIZE {
+                let origin = Position::new(chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+                let pattern = Pattern::from_map(sample, origin);
+                for &(horizontal, vertical) in
+                    &[(false, false), (true, false), (false, true), (true, true)]
+                {
+                    let variant = pattern.flipped(horizontal, vertical);
+                    if !patterns.contains(&variant) {
+                        patterns.push(variant);
+                    }
+                }
+            }
+        }
+        patterns
+    }
+
+    /// `rules[heading][a][b]`: may `b` sit next to `a`, towards `heading`?
+    fn learn_rules(patterns: &[Pattern]) -> Vec<Vec<Vec<bool>>> {
+        Heading::iter()
+            .map(|heading| {
+                patterns
+                    .iter()
+                    .map(|a| patterns.iter().map(|b| compatible(a, b, heading)).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Collapse the chunk with the fewest options left, one at a time. Gives up when a chunk
+    /// runs out of options; there's no backtracking.
+    fn solve(
+        patterns: &[Pattern],
+        rules: &[Vec<Vec<bool>>],
+        rng: &mut RandomNumberGenerator,
+        build_data: &mut BuildData,
+    ) -> bool {
+        let chunks_x = build_data.map.width / CHUNK_SIZE;
+        let chunks_y = build_data.map.height / CHUNK_SIZE;
+        let mut chunks: Vec<Option<usize>> = vec![None; (chunks_x * chunks_y) as usize];
+        fill(&build_data.map.rect(), TileType::Wall, &mut build_data.map);
+
+        loop {
+            let mut candidates: Vec<(i32, i32, Vec<usize>)> = vec![];
+            for chunk_y in 0..chunks_y {
+                for chunk_x in 0..chunks_x {
+                    if chunks[(chunk_y * chunks_x + chunk_x) as usize].is_some() {
+                        continue;
+                    }
+                    let options: Vec<usize> = (0..patterns.len())
+                        .filter(|&option| {
+                            Heading::iter().enumerate().all(|(heading_idx, heading)| {
+                                let neighbor = Vector::unit(heading);
+                                let (x, y) = (chunk_x + neighbor.x, chunk_y + neighbor.y);
+                                if x < 0 || y < 0 || x >= chunks_x || y >= chunks_y {
+                                    return true;
+                                }
+                                chunks[(y * chunks_x + x) as usize]
+                                    .map_or(true, |placed| rules[heading_idx][option][placed])
+                            })
+                        })
+                        .collect();
+                    if options.is_empty() {
+                        return false;
+                    }
+                    let best = candidates.first().map(|(_, _, best)| best.len());
+                    match best {
+                        Some(best) if best < options.len() => {}
+                        Some(best) if best == options.len() => {
+                            candidates.push((chunk_x, chunk_y, options))
+                        }
+                        _ => candidates = vec![(chunk_x, chunk_y, options)],
+                    }
+                }
+            }
+
+            let (chunk_x, chunk_y, options) = match rng.random_slice_entry(&candidates) {
+                Some(candidate) => candidate.clone(),
+                None => return true,
+            };
+            let pattern = *rng.random_slice_entry(&options).unwrap();
+            chunks[(chunk_y * chunks_x + chunk_x) as usize] = Some(pattern);
+
+            let origin = Position::new(chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+            for y in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    build_data.map[&(origin + Vector::new(x, y))] = patterns[pattern].tile(x, y);
+                }
+            }
+            build_data.take_snapshot();
+        }
+    }
+}
+
+impl MetaMapBuilder for WaveFunctionCollapseBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuildData) {
+        let sample = build_data.map.clone();
+        let patterns = Self::learn_patterns(&sample);
+        let rules = Self::learn_rules(&patterns);
+
+        for _ in 0..MAX_ATTEMPTS {
+            if !Self::solve(&patterns, &rules, rng, build_data) {
+                continue;
+            }
+
+            // Add walls around the edges of the map so that it doesn't just cut off weirdly
+            let map = &mut build_data.map;
+            walls_around(&Rect::with_size(0, 0, map.width - 1, map.height - 1), map);
+            build_data.take_snapshot();
+
+            // Keep what's connected to a random floor tile, if that's big enough to be a level
+            let map = &mut build_data.map;
+            if !map.position_set().iter().any(|p| map[p] == TileType::Floor) {
+                continue;
+            }
+            let random_floor = random_position_with_tile(TileType::Floor, map, rng);
+            let threshold = ((map.width * map.height) as f32 * 0.20).round() as usize;
+            if remove_unreachable_areas(&random_floor, map).len() < threshold {
+                continue;
+            }
+            build_data.take_snapshot();
+
+            // The old plans don't apply to the new map
+            build_data.starting_position = None;
+            build_data.rooms = None;
+            build_data.spawn_regions.clear();
+            build_data.spawn_list.clear();
+            build_data.door_positions.clear();
+            return;
+        }
+
+        // Couldn't come up with anything usable, so keep the sample
+        build_data.map = sample;
+        build_data.take_snapshot();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapgen::common::*;
+    use crate::mapgen::wfc::*;
+    use crate::mapgen::{builder_chain, BuilderKind, MapBuilder, MapgenSettings};
+
+    #[test]
+    fn test_wfc_stairs_reachable() {
+        let rex_assets = RexAssets::new();
+        for kind in &[BuilderKind::WfcCave, BuilderKind::WfcOutpost] {
+            for seed in 0..5 {
+                let mut rng = RandomNumberGenerator::seeded(seed);
+                let settings = MapgenSettings::default();
+                let mut chain = builder_chain(*kind, &settings, &mut rng, 80, 43, 4, &rex_assets);
+                chain.build_map(&mut rng);
+
+                let map = chain.get_map();
+                let walkable = walkable_positions(&map);
+                let reachable = connected_region(&chain.get_starting_position(), &map);
+                assert_eq!(
+                    reachable.len(),
+                    walkable.len(),
+                    "{} with seed {} left unreachable areas",
+                    kind,
+                    seed
+                );
+                assert!(
+                    reachable.iter().any(|p| map[p] == TileType::DownStairs),
+                    "{} with seed {} has no stairs",
+                    kind,
+                    seed
+                );
+            }
+        }
+    }
+}
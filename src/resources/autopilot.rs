@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use bracket_lib::prelude::{a_star_search, DijkstraMap};
+use legion::Entity;
+
+use crate::components::{HungerState, Position};
+use crate::resources::Map;
+use crate::util::vector::Vector;
+
+/// Keeps the player moving without a key press for every step, until something needs their
+/// attention. Taking damage or spotting something hidden also turns it off.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Autopilot {
+    Off,
+    On { plan: Plan, watch: Watch },
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Autopilot::Off
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Plan {
+    Explore,
+    Travel {
+        destination: Position,
+    },
+    /// Until fully healed
+    Rest,
+}
+
+/// How things looked on the last step. The autopilot stops when they change for the worse.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Watch {
+    pub hp: i32,
+    pub hunger: HungerState,
+    pub monster_in_view: bool,
+    /// Every item seen since the autopilot was engaged
+    pub items_seen: HashSet<Entity>,
+}
+
+impl Watch {
+    /// Whether the player should take over again, judging by how things look `now`
+    #[must_use]
+    pub fn interrupted_by(&self, now: &Watch) -> bool {
+        now.hp < self.hp
+            || (now.monster_in_view && !self.monster_in_view)
+            || now.hunger != self.hunger
+            || !now.items_seen.is_subset(&self.items_seen)
+    }
+}
+
+/// Next step towards the nearest revealed tile that borders unrevealed ones. `None` once there's
+/// nothing left to explore that can be reached from `from`.
+#[must_use]
+pub fn explore_step(map: &Map, revealed: &HashSet<Position>, from: Position) -> Option<Position> {
+    let frontier: Vec<usize> = revealed
+        .iter()
+        .filter(|&&position| map.contains(position) && map[position].is_walkable())
+        .filter(|&&position| {
+            (-1..=1)
+                .flat_map(|dx| (-1..=1).map(move |dy| position + Vector::new(dx, dy)))
+                .any(|neighbor| map.contains(neighbor) && !revealed.contains(&neighbor))
+        })
+        .map(|&position| map.pos_idx(position))
+        .collect();
+    if frontier.is_empty() {
+        return None;
+    }
+
+    let dijkstra_map = DijkstraMap::new(map.width, map.height, &frontier, map, std::f32::MAX);
+    let from_idx = map.pos_idx(from);
+    if dijkstra_map.map[from_idx] == std::f32::MAX {
+        return None;
+    }
+    DijkstraMap::find_lowest_exit(&dijkstra_map, from_idx, map).map(|idx| map.idx_pos(idx))
+}
+
+/// Next step along the shortest path to `destination`. `None` when already there, or when there's
+/// no way to get there.
+#[must_use]
+pub fn travel_step(map: &Map, from: Position, destination: Position) -> Option<Position> {
+    if from == destination || !map.contains(destination) {
+        return None;
+    }
+    let path = a_star_search(map.pos_idx(from), map.pos_idx(destination), map);
+    if path.success {
+        path.steps.get(1).map(|&idx| map.idx_pos(idx))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use legion::{Entity, World};
+
+    use crate::components::{HungerState, Position};
+    use crate::resources::autopilot::{explore_step, travel_step, Watch};
+    use crate::resources::Map;
+
+    fn calm() -> Watch {
+        Watch {
+            hp: 20,
+            hunger: HungerState::Normal,
+            monster_in_view: false,
+            items_seen: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn losing_hp_interrupts() {
+        let before = calm();
+        assert!(!before.interrupted_by(&calm()));
+        assert!(before.interrupted_by(&Watch { hp: 19, ..calm() }));
+        // Healing up while resting is the whole point
+        assert!(!before.interrupted_by(&Watch { hp: 21, ..calm() }));
+    }
+
+    #[test]
+    fn new_sights_interrupt() {
+        let before = calm();
+        assert!(before.interrupted_by(&Watch {
+            monster_in_view: true,
+            ..calm()
+        }));
+        assert!(before.interrupted_by(&Watch {
+            hunger: HungerState::Hungry,
+            ..calm()
+        }));
+
+        let item: Entity = World::default().push((0,));
+        let seen = Watch {
+            items_seen: vec![item].into_iter().collect(),
+            ..calm()
+        };
+        assert!(before.interrupted_by(&seen));
+        assert!(!seen.interrupted_by(&seen.clone()));
+        // Monsters that stay in view don't keep interrupting
+        let watching = Watch {
+            monster_in_view: true,
+            ..calm()
+        };
+        assert!(!watching.interrupted_by(&watching.clone()));
+    }
+
+    #[test]
+    fn explores_towards_the_unknown() {
+        let (map, markers) = Map::from_ascii_with_markers(
+            "
+            ##########
+            #@.......#
+            ##########
+            ",
+        );
+        let start = *markers.keys().next().unwrap();
+        let mut revealed: HashSet<Position> = map
+            .position_set()
+            .into_iter()
+            .filter(|position| position.x <= 4)
+            .collect();
+        assert_eq!(
+            explore_step(&map, &revealed, start),
+            Some(Position::new(2, 1))
+        );
+
+        revealed = map.position_set();
+        assert_eq!(explore_step(&map, &revealed, start), None);
+    }
+
+    #[test]
+    fn travels_around_walls() {
+        let (map, markers) = Map::from_ascii_with_markers(
+            "
+            #####
+            #@#>#
+            #...#
+            #####
+            ",
+        );
+        let start = *markers.keys().next().unwrap();
+        let stairs = Position::new(3, 1);
+        assert_eq!(travel_step(&map, start, stairs), Some(Position::new(2, 2)));
+        assert_eq!(travel_step(&map, stairs, stairs), None);
+        assert_eq!(travel_step(&map, start, Position::new(2, 1)), None);
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Counts turns since the start of the run. A turn is over once the monsters have had theirs.
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "a879a037-f8ef-4d36-b9f5-d755a851f2cc"]
+pub struct Clock {
+    pub turn: u32,
+}
@@ -1,10 +1,347 @@
+use bracket_lib::prelude::*;
 use serde::{Deserialize, Serialize};
 use type_uuid::TypeUuid;
 
+use crate::resources::RolledAppearance;
+use crate::util::i18n::{tr_name, tr_spans, Span};
+
+/// Older entries are dropped, so that saves don't keep growing
+pub const MAX_LOG_ENTRIES: usize = 500;
+
+/// What a message is about, which decides its color
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LogCategory {
+    Info,
+    Combat,
+    Status,
+    Item,
+    Discovery,
+}
+
+impl LogCategory {
+    #[must_use]
+    pub fn color(self) -> RGB {
+        match self {
+            LogCategory::Info => RGB::named(WHITE),
+            LogCategory::Combat => RGB::named(RED),
+            LogCategory::Status => RGB::named(YELLOW),
+            LogCategory::Item => RGB::named(CYAN),
+            LogCategory::Discovery => RGB::named(MAGENTA),
+        }
+    }
+
+    /// For names and numbers within a message
+    #[must_use]
+    pub fn highlight_color(self) -> RGB {
+        match self {
+            LogCategory::Info => RGB::named(YELLOW),
+            _ => RGB::named(WHITE),
+        }
+    }
+}
+
+/// Everything that can show up in the log
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LogKind {
+    Welcome,
+    NoLongerWellFed,
+    Hungry,
+    Starving,
+    Ate,
+    HungerPang,
+    ItemDamage,
+    Hit,
+    Miss,
+    TrapDamage,
+    Healing,
+    Death,
+    Confused,
+    ConfusionOver,
+    PickupNothingHere,
+    PickupGold,
+    Pickup,
+    PickupBlocked,
+    Drop,
+    Equip,
+    Unequip,
+    TooFarAway,
+    NoValidTargets,
+    NoStairsHere,
+    MovedToNextLevel,
+    DoorOpened,
+    DoorClosed,
+    NoDoorToClose,
+    NothingToExplore,
+    NoKnownStairs,
+    MagicMapping,
+    Spotted,
+    NothingFound,
+    CannotRest,
+    EntryTriggered,
+    ItemIdentified,
+    CannotRemoveCursed,
+    CurseRemoved,
+    Bought,
+    Sold,
+    CannotAfford,
+    PurchaseBlocked,
+}
+
+impl LogKind {
+    #[must_use]
+    pub fn category(self) -> LogCategory {
+        use LogKind::*;
+        match self {
+            Welcome | PickupNothingHere | PickupBlocked | TooFarAway | NoValidTargets
+            | NoStairsHere | DoorOpened | DoorClosed | NoDoorToClose | NothingToExplore
+            | NoKnownStairs | NothingFound | CannotRest | CannotAfford | PurchaseBlocked => {
+                LogCategory::Info
+            }
+            HungerPang | ItemDamage | Hit | Miss | TrapDamage | Death | EntryTriggered => {
+                LogCategory::Combat
+            }
+            NoLongerWellFed | Hungry | Starving | Healing | Confused | ConfusionOver => {
+                LogCategory::Status
+            }
+            Ate | PickupGold | Pickup | Drop | Equip | Unequip | ItemIdentified
+            | CannotRemoveCursed | CurseRemoved | Bought | Sold => LogCategory::Item,
+            MovedToNextLevel | MagicMapping | Spotted => LogCategory::Discovery,
+        }
+    }
+}
+
+/// Who took part in a logged event, as they were at the time. Entities may be gone by the time the
+/// log is read, so only what's needed for display is kept.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct LogEntity {
+    pub name: String,
+    pub is_player: bool,
+}
+
+/// An item as the player knew it at the time. Items logged before they were identified keep
+/// showing up under their appearance, so that old messages don't give anything away.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LogItem {
+    pub name: String,
+    /// Only set while the item type wasn't identified
+    pub appearance: Option<RolledAppearance>,
+    pub enchantment: i32,
+}
+
+impl LogItem {
+    fn text(&self) -> String {
+        let name = match &self.appearance {
+            Some(appearance) => appearance.text(),
+            None => tr_name(&self.name),
+        };
+        if self.enchantment == 0 {
+            name
+        } else {
+            format!("{:+} {}", self.enchantment, name)
+        }
+    }
+}
+
+/// One thing that happened, turned into text only when shown
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub kind: LogKind,
+    pub actor: Option<LogEntity>,
+    pub target: Option<LogEntity>,
+    pub item: Option<LogItem>,
+    pub amount: Option<i32>,
+    /// The labels that led to this, outermost first
+    pub causes: Vec<String>,
+}
+
+impl LogRecord {
+    #[must_use]
+    pub fn new(kind: LogKind) -> Self {
+        LogRecord {
+            kind,
+            actor: None,
+            target: None,
+            item: None,
+            amount: None,
+            causes: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn actor(mut self, actor: LogEntity) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    #[must_use]
+    pub fn target(mut self, target: LogEntity) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    #[must_use]
+    pub fn item(mut self, item: LogItem) -> Self {
+        self.item = Some(item);
+        self
+    }
+
+    #[must_use]
+    pub fn amount(mut self, amount: i32) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    #[must_use]
+    pub fn caused_by(mut self, causes: Vec<String>) -> Self {
+        self.causes = causes;
+        self
+    }
+
+    fn by_player(&self) -> bool {
+        self.actor.as_ref().map_or(false, |actor| actor.is_player)
+    }
+
+    fn to_player(&self) -> bool {
+        self.target
+            .as_ref()
+            .map_or(false, |target| target.is_player)
+    }
+
+    /// Which catalog entry tells about this
+    fn key(&self) -> &'static str {
+        use LogKind::*;
+        let by_player = self.by_player();
+        let to_player = self.to_player();
+        match self.kind {
+            Welcome => "log.welcome",
+            NoLongerWellFed => "log.no_longer_well_fed",
+            Hungry => "log.hungry",
+            Starving => "log.starving",
+            Ate => "log.ate",
+            HungerPang if to_player => "log.hunger_pang.you",
+            HungerPang => "log.hunger_pang.other",
+            ItemDamage => "log.item_damage",
+            Hit if by_player => "log.hit.by_you",
+            Hit if to_player => "log.hit.to_you",
+            Hit => "log.hit.other",
+            Miss if by_player => "log.miss.by_you",
+            Miss if to_player => "log.miss.to_you",
+            Miss => "log.miss.other",
+            TrapDamage if by_player => "log.trap_damage.you",
+            TrapDamage => "log.trap_damage.other",
+            Healing => "log.healing",
+            Death => "log.death",
+            Confused => "log.confused",
+            ConfusionOver => "log.confusion_over",
+            PickupNothingHere => "log.pickup_nothing_here",
+            PickupGold => "log.pickup_gold",
+            Pickup if by_player => "log.pickup.you",
+            Pickup => "log.pickup.other",
+            PickupBlocked => "log.pickup_blocked",
+            Drop if by_player => "log.drop.you",
+            Drop => "log.drop.other",
+            Equip if by_player => "log.equip.you",
+            Equip => "log.equip.other",
+            Unequip if by_player => "log.unequip.you",
+            Unequip => "log.unequip.other",
+            TooFarAway => "log.too_far_away",
+            NoValidTargets => "log.no_valid_targets",
+            NoStairsHere => "log.no_stairs_here",
+            MovedToNextLevel => "log.moved_to_next_level",
+            DoorOpened => "log.door_opened",
+            DoorClosed => "log.door_closed",
+            NoDoorToClose => "log.no_door_to_close",
+            NothingToExplore => "log.nothing_to_explore",
+            NoKnownStairs => "log.no_known_stairs",
+            MagicMapping => "log.magic_mapping",
+            Spotted => "log.spotted",
+            NothingFound => "log.nothing_found",
+            CannotRest => "log.cannot_rest",
+            EntryTriggered if by_player => "log.entry_triggered.you",
+            EntryTriggered => "log.entry_triggered.other",
+            ItemIdentified => "log.item_identified",
+            CannotRemoveCursed => "log.cannot_remove_cursed",
+            CurseRemoved => "log.curse_removed",
+            Bought => "log.bought",
+            Sold => "log.sold",
+            CannotAfford => "log.cannot_afford",
+            PurchaseBlocked => "log.purchase_blocked",
+        }
+    }
+
+    /// The message in the chosen language, split up so that names and numbers can be drawn
+    /// differently from the rest
+    #[must_use]
+    pub fn spans(&self) -> Vec<Span> {
+        let name = |entity: &Option<LogEntity>| {
+            entity
+                .as_ref()
+                .map(|entity| tr_name(&entity.name))
+                .unwrap_or_default()
+        };
+        tr_spans(
+            self.key(),
+            &[
+                ("actor", name(&self.actor)),
+                ("target", name(&self.target)),
+                (
+                    "item",
+                    self.item.as_ref().map(LogItem::text).unwrap_or_default(),
+                ),
+                (
+                    "amount",
+                    self.amount
+                        .map(|amount| amount.to_string())
+                        .unwrap_or_default(),
+                ),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.spans().into_iter().map(|span| span.text).collect()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// When the message was last repeated
+    pub turn: u32,
+    pub record: LogRecord,
+    /// How many times in a row the message came up
+    pub count: u32,
+}
+
+impl LogEntry {
+    #[must_use]
+    pub fn category(&self) -> LogCategory {
+        self.record.kind.category()
+    }
+
+    /// The message as spans, with the repeat count at the end
+    #[must_use]
+    pub fn spans(&self) -> Vec<Span> {
+        let mut spans = self.record.spans();
+        if self.count > 1 {
+            spans.push(Span {
+                text: format!(" x{}", self.count),
+                highlight: false,
+            });
+        }
+        spans
+    }
+
+    #[must_use]
+    pub fn display(&self) -> String {
+        self.spans().into_iter().map(|span| span.text).collect()
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, TypeUuid)]
 #[uuid = "4f44e6cd-11b0-46fe-9bf9-e4bf42a3a8dd"]
 pub struct GameLog {
-    pub entries: Vec<String>,
+    pub entries: Vec<LogEntry>,
 }
 
 impl Default for GameLog {
@@ -14,7 +351,95 @@ impl Default for GameLog {
 }
 
 impl GameLog {
-    pub fn push<S: ToString>(&mut self, msg: S) {
-        self.entries.push(msg.to_string());
+    /// Adds a record, or counts it again if it reads the same as the last one
+    pub fn push(&mut self, turn: u32, record: LogRecord) {
+        match self.entries.last_mut() {
+            Some(last)
+                if last.record.kind == record.kind && last.record.text() == record.text() =>
+            {
+                last.count += 1;
+                last.turn = turn;
+            }
+            _ => self.entries.push(LogEntry {
+                turn,
+                record,
+                count: 1,
+            }),
+        }
+
+        if self.entries.len() > MAX_LOG_ENTRIES {
+            let excess = self.entries.len() - MAX_LOG_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resources::gamelog::*;
+
+    fn goblin() -> LogEntity {
+        LogEntity {
+            name: "Goblin".to_string(),
+            is_player: false,
+        }
+    }
+
+    fn player() -> LogEntity {
+        LogEntity {
+            name: "Player".to_string(),
+            is_player: true,
+        }
+    }
+
+    #[test]
+    fn repeats_are_coalesced() {
+        let hit = LogRecord::new(LogKind::Hit)
+            .actor(player())
+            .target(goblin())
+            .amount(2);
+        let mut log = GameLog::default();
+        for turn in 1..=3 {
+            log.push(turn, hit.clone());
+        }
+        log.push(4, LogRecord::new(LogKind::NoStairsHere));
+        log.push(5, hit.clone().amount(3));
+
+        assert_eq!(log.entries.len(), 3);
+        assert_eq!(log.entries[0].display(), "You hit Goblin, for 2 hp. x3");
+        assert_eq!(log.entries[0].turn, 3);
+        assert_eq!(log.entries[2].record.amount, Some(3));
+    }
+
+    #[test]
+    fn old_entries_are_dropped() {
+        let mut log = GameLog::default();
+        for turn in 0..(MAX_LOG_ENTRIES as u32 + 10) {
+            log.push(
+                turn,
+                LogRecord::new(LogKind::PickupGold).amount(turn as i32),
+            );
+        }
+        assert_eq!(log.entries.len(), MAX_LOG_ENTRIES);
+        assert_eq!(log.entries[0].record.amount, Some(10));
+    }
+
+    #[test]
+    fn names_are_highlighted() {
+        let spans = LogRecord::new(LogKind::Hit)
+            .actor(goblin())
+            .target(player())
+            .amount(4)
+            .spans();
+        let highlighted: Vec<_> = spans
+            .iter()
+            .filter(|span| span.highlight)
+            .map(|span| span.text.as_str())
+            .collect();
+        assert_eq!(highlighted, vec!["Goblin", "4"]);
+        assert_eq!(
+            spans.into_iter().map(|span| span.text).collect::<String>(),
+            "Goblin hits you, for 4 hp."
+        );
     }
 }
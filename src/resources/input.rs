@@ -4,6 +4,8 @@ use bracket_lib::prelude::*;
 pub struct Input {
     pub key: Option<VirtualKeyCode>,
     pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
     pub mouse_pos: Point,
     pub left_click: bool,
 }
@@ -13,6 +15,8 @@ impl From<&BTerm> for Input {
         Input {
             key: term.key,
             shift: term.shift,
+            control: term.control,
+            alt: term.alt,
             mouse_pos: term.mouse_point(),
             left_click: term.left_click,
         }
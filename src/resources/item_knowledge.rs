@@ -0,0 +1,195 @@
+use std::collections::{HashMap, HashSet};
+
+use bracket_lib::prelude::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+use crate::components::{Appearance, Name};
+use crate::util::i18n::{tr, tr_args, tr_name};
+
+/// Catalog keys are `appearance.adjective.<adjective>`
+const POTION_ADJECTIVES: &[&str] = &[
+    "murky", "bubbling", "violet", "golden", "smoking", "viscous", "fizzy", "pale", "crimson",
+    "oily", "glowing", "cloudy",
+];
+
+const SCROLL_SYLLABLES: &[&str] = &[
+    "ka", "zor", "ux", "vel", "lo", "ny", "ash", "ith", "ru", "qa", "mel", "xy", "zzy", "pho",
+    "gar", "em", "bo", "tis",
+];
+
+/// How an unidentified item type looks. Only the rolled words are stored, so it can be put into
+/// the current language whenever it's shown.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
+pub struct RolledAppearance {
+    appearance: Appearance,
+    /// The adjective of a potion, or the label of a scroll
+    word: String,
+    /// Tells apart appearances rolled after the unique ones ran out
+    number: Option<usize>,
+}
+
+impl RolledAppearance {
+    #[must_use]
+    pub fn text(&self) -> String {
+        let text = match self.appearance {
+            Appearance::Potion => tr_args(
+                "appearance.potion",
+                &[(
+                    "adjective",
+                    tr(&format!("appearance.adjective.{}", self.word)),
+                )],
+            ),
+            Appearance::Scroll => {
+                tr_args("appearance.scroll", &[("label", self.word.to_uppercase())])
+            }
+        };
+        match self.number {
+            Some(number) => format!("{} #{}", text, number),
+            None => text,
+        }
+    }
+}
+
+/// What the player knows about item types during the current run.
+/// Keyed by the true `Name` of the item type.
+#[derive(Clone, Default, Serialize, Deserialize, TypeUuid)]
+#[uuid = "95725adc-25ae-4c84-89cf-4fa7f84b5598"]
+pub struct ItemKnowledge {
+    appearances: HashMap<Name, RolledAppearance>,
+    identified: HashSet<Name>,
+}
+
+impl ItemKnowledge {
+    /// Generate a randomized appearance for the item type `name`, unless it already has one
+    pub fn assign_appearance(
+        &mut self,
+        name: &Name,
+        appearance: Appearance,
+        rng: &mut RandomNumberGenerator,
+    ) {
+        if self.appearances.contains_key(name) {
+            return;
+        }
+        let taken: HashSet<&RolledAppearance> = self.appearances.values().collect();
+        let unique = (0..100)
+            .map(|_| Self::roll_appearance(appearance, rng))
+            .find(|candidate| !taken.contains(candidate));
+        // If we ran out of unique descriptions, numbering is ugly, but at least it's unambiguous
+        let candidate = unique.unwrap_or_else(|| RolledAppearance {
+            number: Some(self.appearances.len()),
+            ..Self::roll_appearance(appearance, rng)
+        });
+        self.appearances.insert(name.clone(), candidate);
+    }
+
+    fn roll_appearance(
+        appearance: Appearance,
+        rng: &mut RandomNumberGenerator,
+    ) -> RolledAppearance {
+        let word = match appearance {
+            Appearance::Potion => (*rng.random_slice_entry(POTION_ADJECTIVES).unwrap()).to_string(),
+            Appearance::Scroll => {
+                let syllables = rng.range(2, 4);
+                (0..syllables)
+                    .map(|_| *rng.random_slice_entry(SCROLL_SYLLABLES).unwrap())
+                    .collect()
+            }
+        };
+        RolledAppearance {
+            appearance,
+            word,
+            number: None,
+        }
+    }
+
+    pub fn appearance(&self, name: &Name) -> Option<&RolledAppearance> {
+        self.appearances.get(name)
+    }
+
+    pub fn is_identified(&self, name: &Name) -> bool {
+        !self.appearances.contains_key(name) || self.identified.contains(name)
+    }
+
+    pub fn identify(&mut self, name: &Name) {
+        self.identified.insert(name.clone());
+    }
+
+    /// The name of an item type as the player currently knows it
+    pub fn display_name(&self, name: &Name) -> String {
+        if self.is_identified(name) {
+            tr_name(name)
+        } else {
+            self.appearances[name].text()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bracket_lib::prelude::RandomNumberGenerator;
+    use strum::IntoEnumIterator;
+
+    use crate::components::{Appearance, Name};
+    use crate::resources::item_knowledge::{ItemKnowledge, RolledAppearance, POTION_ADJECTIVES};
+    use crate::util::i18n::{Catalog, Language};
+
+    #[test]
+    fn unidentified_items_use_their_appearance() {
+        let mut knowledge = ItemKnowledge::default();
+        let name = Name::from("Fireball Scroll".to_string());
+        let mut rng = RandomNumberGenerator::seeded(1);
+        knowledge.assign_appearance(&name, Appearance::Scroll, &mut rng);
+
+        assert!(!knowledge.is_identified(&name));
+        assert!(knowledge.display_name(&name).starts_with("scroll labeled "));
+
+        knowledge.identify(&name);
+        assert!(knowledge.is_identified(&name));
+        assert_eq!(knowledge.display_name(&name), "Fireball Scroll");
+    }
+
+    #[test]
+    fn appearances_are_unique() {
+        let mut knowledge = ItemKnowledge::default();
+        let mut rng = RandomNumberGenerator::seeded(1);
+        let names: Vec<Name> = (0..20)
+            .map(|i| Name::from(format!("Potion {}", i)))
+            .collect();
+        for name in &names {
+            knowledge.assign_appearance(name, Appearance::Potion, &mut rng);
+        }
+        let mut appearances: Vec<String> = names
+            .iter()
+            .flat_map(|n| knowledge.appearance(n))
+            .map(RolledAppearance::text)
+            .collect();
+        appearances.sort();
+        appearances.dedup();
+        assert_eq!(appearances.len(), names.len());
+    }
+
+    #[test]
+    fn every_potion_adjective_is_translated() {
+        for language in Language::iter() {
+            let catalog = Catalog::load(language);
+            for adjective in POTION_ADJECTIVES {
+                let key = format!("appearance.adjective.{}", adjective);
+                assert!(
+                    catalog.template(&key).is_some(),
+                    "{} catalog is missing {}",
+                    language.code(),
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn items_without_appearance_are_always_identified() {
+        let knowledge = ItemKnowledge::default();
+        let name = Name::from("Dagger".to_string());
+        assert!(knowledge.is_identified(&name));
+        assert_eq!(knowledge.display_name(&name), "Dagger");
+    }
+}
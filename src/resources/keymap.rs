@@ -0,0 +1,584 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use bracket_lib::prelude::VirtualKeyCode;
+use strum_macros::{Display, EnumString};
+
+use crate::resources::Input;
+use crate::util::vector::{Heading, Vector};
+
+/// Groups of run states that share bindings
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Display, EnumString)]
+pub enum KeymapContext {
+    Game,
+    Menu,
+    Inventory,
+    Targeting,
+}
+
+/// Everything a key can be bound to
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Display, EnumString)]
+pub enum KeyAction {
+    MoveNorth,
+    MoveNorthEast,
+    MoveEast,
+    MoveSouthEast,
+    MoveSouth,
+    MoveSouthWest,
+    MoveWest,
+    MoveNorthWest,
+    SkipTurn,
+    Rest,
+    Search,
+    AutoExplore,
+    TravelToStairs,
+    DownStairs,
+    CloseDoor,
+    PickUp,
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    ShowLog,
+    ShowCharacter,
+    Look,
+    ShowKeymap,
+    SaveGame,
+
+    MenuUp,
+    MenuDown,
+    MenuSelect,
+    SwitchVendorMode,
+    NextTarget,
+    Confirm,
+    Cancel,
+}
+
+impl KeyAction {
+    /// Which way the move actions go. While targeting, they move the cursor instead of the player.
+    #[must_use]
+    pub fn direction(self) -> Option<Vector> {
+        match self {
+            KeyAction::MoveNorth => Some(Heading::North.into()),
+            KeyAction::MoveEast => Some(Heading::East.into()),
+            KeyAction::MoveSouth => Some(Heading::South.into()),
+            KeyAction::MoveWest => Some(Heading::West.into()),
+            KeyAction::MoveNorthEast => Some(Heading::North + Heading::East),
+            KeyAction::MoveNorthWest => Some(Heading::North + Heading::West),
+            KeyAction::MoveSouthEast => Some(Heading::South + Heading::East),
+            KeyAction::MoveSouthWest => Some(Heading::South + Heading::West),
+            _ => None,
+        }
+    }
+}
+
+/// Keys that can appear in the keymap file. Anything else is rejected when loading.
+pub const KEYS: &[VirtualKeyCode] = &[
+    VirtualKeyCode::A,
+    VirtualKeyCode::B,
+    VirtualKeyCode::C,
+    VirtualKeyCode::D,
+    VirtualKeyCode::E,
+    VirtualKeyCode::F,
+    VirtualKeyCode::G,
+    VirtualKeyCode::H,
+    VirtualKeyCode::I,
+    VirtualKeyCode::J,
+    VirtualKeyCode::K,
+    VirtualKeyCode::L,
+    VirtualKeyCode::M,
+    VirtualKeyCode::N,
+    VirtualKeyCode::O,
+    VirtualKeyCode::P,
+    VirtualKeyCode::Q,
+    VirtualKeyCode::R,
+    VirtualKeyCode::S,
+    VirtualKeyCode::T,
+    VirtualKeyCode::U,
+    VirtualKeyCode::V,
+    VirtualKeyCode::W,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+    VirtualKeyCode::Key0,
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+    VirtualKeyCode::Numpad0,
+    VirtualKeyCode::Numpad1,
+    VirtualKeyCode::Numpad2,
+    VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4,
+    VirtualKeyCode::Numpad5,
+    VirtualKeyCode::Numpad6,
+    VirtualKeyCode::Numpad7,
+    VirtualKeyCode::Numpad8,
+    VirtualKeyCode::Numpad9,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::PageUp,
+    VirtualKeyCode::PageDown,
+    VirtualKeyCode::Insert,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Escape,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Back,
+    VirtualKeyCode::Period,
+    VirtualKeyCode::Comma,
+    VirtualKeyCode::Slash,
+    VirtualKeyCode::Backslash,
+    VirtualKeyCode::Semicolon,
+    VirtualKeyCode::Apostrophe,
+    VirtualKeyCode::Minus,
+    VirtualKeyCode::Equals,
+    VirtualKeyCode::LBracket,
+    VirtualKeyCode::RBracket,
+    VirtualKeyCode::Grave,
+    VirtualKeyCode::F1,
+    VirtualKeyCode::F2,
+    VirtualKeyCode::F3,
+    VirtualKeyCode::F4,
+    VirtualKeyCode::F5,
+    VirtualKeyCode::F6,
+    VirtualKeyCode::F7,
+    VirtualKeyCode::F8,
+    VirtualKeyCode::F9,
+    VirtualKeyCode::F10,
+    VirtualKeyCode::F11,
+    VirtualKeyCode::F12,
+];
+
+/// A key, and the modifiers that have to be held down with it
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct KeyBinding {
+    pub key: VirtualKeyCode,
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    #[must_use]
+    pub fn new(key: VirtualKeyCode) -> Self {
+        KeyBinding {
+            key,
+            shift: false,
+            control: false,
+            alt: false,
+        }
+    }
+
+    #[must_use]
+    pub fn shift(key: VirtualKeyCode) -> Self {
+        KeyBinding {
+            shift: true,
+            ..KeyBinding::new(key)
+        }
+    }
+
+    /// The key being pressed right now, if any
+    #[must_use]
+    pub fn pressed(input: &Input) -> Option<Self> {
+        Some(KeyBinding {
+            key: input.key?,
+            shift: input.shift,
+            control: input.control,
+            alt: input.alt,
+        })
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.control {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{:?}", self.key)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key_name = parts.pop().unwrap_or_default();
+        let key = KEYS
+            .iter()
+            .find(|key| format!("{:?}", key) == key_name)
+            .ok_or_else(|| format!("Unknown key {}", key_name))?;
+        let mut binding = KeyBinding::new(*key);
+        for modifier in parts {
+            match modifier {
+                "Ctrl" => binding.control = true,
+                "Alt" => binding.alt = true,
+                "Shift" => binding.shift = true,
+                _ => return Err(format!("Unknown modifier {}", modifier)),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+/// Which keys do what, in each `KeymapContext`.
+///
+/// Saved as one line per action, like `Game.DownStairs = Shift+Period`. Actions can have any
+/// number of bindings, separated by commas. Actions missing from the file keep their default
+/// bindings.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Keymap {
+    bindings: BTreeMap<(KeymapContext, KeyAction), Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyAction::*;
+        use KeymapContext::*;
+        use VirtualKeyCode as Key;
+
+        let game = vec![
+            (MoveNorth, vec![Key::Up, Key::K, Key::Numpad8]),
+            (MoveNorthEast, vec![Key::Y, Key::Numpad9]),
+            (MoveEast, vec![Key::Right, Key::L, Key::Numpad6]),
+            (MoveSouthEast, vec![Key::N, Key::Numpad3]),
+            (MoveSouth, vec![Key::Down, Key::J, Key::Numpad2]),
+            (MoveSouthWest, vec![Key::B, Key::Numpad1]),
+            (MoveWest, vec![Key::Left, Key::H, Key::Numpad4]),
+            (MoveNorthWest, vec![Key::U, Key::Numpad7]),
+            (SkipTurn, vec![Key::Period, Key::Numpad5]),
+            (Rest, vec![Key::Z]),
+            (Search, vec![Key::S]),
+            (AutoExplore, vec![Key::O]),
+            (TravelToStairs, vec![Key::T]),
+            (CloseDoor, vec![Key::C]),
+            (PickUp, vec![Key::G]),
+            (ShowInventory, vec![Key::I]),
+            (ShowDropItem, vec![Key::D]),
+            (ShowRemoveItem, vec![Key::R]),
+            (ShowLog, vec![Key::M]),
+            (ShowCharacter, vec![Key::P]),
+            (Look, vec![Key::X]),
+            (SaveGame, vec![Key::Escape]),
+        ];
+        let menu = vec![
+            (MenuUp, vec![Key::Up, Key::K, Key::Numpad8]),
+            (MenuDown, vec![Key::Down, Key::J, Key::Numpad2]),
+            (MenuSelect, vec![Key::Return]),
+            (Cancel, vec![Key::Escape]),
+        ];
+        let inventory = vec![
+            (SwitchVendorMode, vec![Key::Tab]),
+            (Cancel, vec![Key::Escape]),
+        ];
+        let targeting = vec![
+            (NextTarget, vec![Key::Tab]),
+            (Confirm, vec![Key::Return]),
+            (Cancel, vec![Key::Escape]),
+        ];
+
+        let mut bindings = BTreeMap::new();
+        for (context, actions) in vec![
+            (Game, game),
+            (Menu, menu),
+            (Inventory, inventory),
+            (Targeting, targeting),
+        ] {
+            for (action, keys) in actions {
+                bindings.insert(
+                    (context, action),
+                    keys.into_iter().map(KeyBinding::new).collect(),
+                );
+            }
+        }
+        bindings.insert((Game, DownStairs), vec![KeyBinding::shift(Key::Period)]);
+        bindings.insert((Game, ShowKeymap), vec![KeyBinding::shift(Key::Slash)]);
+
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// The default keymap, with whatever the player changed on top
+    #[must_use]
+    pub fn load() -> Self {
+        let mut keymap = Keymap::default();
+        if let Some(config) = storage::read() {
+            for error in keymap.apply_config(&config) {
+                println!("Ignoring keymap entry: {}", error);
+            }
+        }
+        keymap
+    }
+
+    pub fn save(&self) {
+        storage::write(&self.to_config());
+    }
+
+    #[must_use]
+    pub fn action(&self, context: KeymapContext, input: &Input) -> Option<KeyAction> {
+        let pressed = KeyBinding::pressed(input)?;
+        self.bindings
+            .iter()
+            .find(|((c, _), bindings)| *c == context && bindings.contains(&pressed))
+            .map(|((_, action), _)| *action)
+    }
+
+    /// Every action with its bindings, in a stable order
+    pub fn entries(&self) -> impl Iterator<Item = (KeymapContext, KeyAction, &[KeyBinding])> {
+        self.bindings
+            .iter()
+            .map(|((context, action), bindings)| (*context, *action, bindings.as_slice()))
+    }
+
+    #[must_use]
+    pub fn describe(&self, context: KeymapContext, action: KeyAction) -> String {
+        self.bindings
+            .get(&(context, action))
+            .map(|bindings| {
+                bindings
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Make `binding` the only way to trigger `action`. An action in the same context that had
+    /// `binding` takes over the old bindings of `action` instead, so that it's never ambiguous and
+    /// nothing is left without a key. Does nothing if `action` has no bindings to hand over.
+    pub fn rebind(&mut self, context: KeymapContext, action: KeyAction, binding: KeyBinding) {
+        let old_bindings: Vec<KeyBinding> = match self.bindings.get(&(context, action)) {
+            Some(bindings) => bindings.iter().copied().filter(|b| *b != binding).collect(),
+            None => return,
+        };
+        let displaced = self
+            .bindings
+            .iter()
+            .find(|((c, a), bindings)| *c == context && *a != action && bindings.contains(&binding))
+            .map(|((_, a), _)| *a);
+        if let Some(displaced) = displaced {
+            let bindings = self.bindings.get_mut(&(context, displaced)).unwrap();
+            bindings.retain(|b| *b != binding);
+            if bindings.is_empty() {
+                if old_bindings.is_empty() {
+                    bindings.push(binding);
+                    return;
+                }
+                *bindings = old_bindings;
+            }
+        }
+        self.bindings.insert((context, action), vec![binding]);
+    }
+
+    fn to_config(&self) -> String {
+        self.entries()
+            .map(|(context, action, _)| {
+                format!(
+                    "{}.{} = {}\n",
+                    context,
+                    action,
+                    self.describe(context, action)
+                )
+            })
+            .collect()
+    }
+
+    /// Returns what couldn't be understood
+    fn apply_config(&mut self, config: &str) -> Vec<String> {
+        let mut errors = vec![];
+        for line in config.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(error) = self.apply_config_line(line) {
+                errors.push(format!("{} ({})", line, error));
+            }
+        }
+        errors
+    }
+
+    fn apply_config_line(&mut self, line: &str) -> Result<(), String> {
+        let mut sides = line.splitn(2, '=');
+        let name = sides.next().unwrap_or_default().trim();
+        let keys = sides.next().ok_or("missing =")?.trim();
+
+        let mut name_parts = name.splitn(2, '.');
+        let context = KeymapContext::from_str(name_parts.next().unwrap_or_default())
+            .map_err(|_| "unknown context")?;
+        let action = KeyAction::from_str(name_parts.next().unwrap_or_default())
+            .map_err(|_| "unknown action")?;
+
+        let bindings = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(KeyBinding::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if bindings.is_empty() {
+            return Err("no keys".to_string());
+        }
+        let entry = self
+            .bindings
+            .get_mut(&(context, action))
+            .ok_or("action not available there")?;
+        *entry = bindings;
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    const KEYMAP: &str = "./keymap.cfg";
+
+    pub fn read() -> Option<String> {
+        std::fs::read_to_string(KEYMAP).ok()
+    }
+
+    pub fn write(config: &str) {
+        if let Err(error) = std::fs::write(KEYMAP, config) {
+            println!("Failed to save keymap: {}", error);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    const KEYMAP: &str = "keymap";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read() -> Option<String> {
+        local_storage()?.get_item(KEYMAP).ok()?
+    }
+
+    pub fn write(config: &str) {
+        if let Some(storage) = local_storage() {
+            if storage.set_item(KEYMAP, config).is_err() {
+                println!("Failed to save keymap");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bracket_lib::prelude::{Point, VirtualKeyCode};
+
+    use crate::resources::keymap::*;
+
+    fn press(key: VirtualKeyCode, shift: bool) -> Input {
+        Input {
+            key: Some(key),
+            shift,
+            control: false,
+            alt: false,
+            mouse_pos: Point::new(0, 0),
+            left_click: false,
+        }
+    }
+
+    #[test]
+    fn modifiers_must_match() {
+        let keymap = Keymap::default();
+        let game = KeymapContext::Game;
+        assert_eq!(
+            keymap.action(game, &press(VirtualKeyCode::Period, false)),
+            Some(KeyAction::SkipTurn)
+        );
+        assert_eq!(
+            keymap.action(game, &press(VirtualKeyCode::Period, true)),
+            Some(KeyAction::DownStairs)
+        );
+        assert_eq!(
+            keymap.action(KeymapContext::Menu, &press(VirtualKeyCode::C, false)),
+            None
+        );
+    }
+
+    #[test]
+    fn rebinding_swaps_keys() {
+        let mut keymap = Keymap::default();
+        let game = KeymapContext::Game;
+        keymap.rebind(game, KeyAction::PickUp, KeyBinding::new(VirtualKeyCode::C));
+        assert_eq!(
+            keymap.action(game, &press(VirtualKeyCode::C, false)),
+            Some(KeyAction::PickUp)
+        );
+        assert_eq!(
+            keymap.action(game, &press(VirtualKeyCode::G, false)),
+            Some(KeyAction::CloseDoor)
+        );
+
+        // The old bindings that aren't swapped are freed up
+        keymap.rebind(
+            game,
+            KeyAction::MoveNorth,
+            KeyBinding::new(VirtualKeyCode::K),
+        );
+        assert_eq!(keymap.describe(game, KeyAction::MoveNorth), "K");
+        assert_eq!(keymap.action(game, &press(VirtualKeyCode::Up, false)), None);
+    }
+
+    #[test]
+    fn rebinding_never_leaves_an_action_without_keys() {
+        let defaults = Keymap::default();
+        for (context, action, _) in defaults.entries() {
+            for (_, _, bindings) in defaults.entries().filter(|(c, _, _)| *c == context) {
+                for &binding in bindings {
+                    let mut keymap = defaults.clone();
+                    keymap.rebind(context, action, binding);
+                    assert!(keymap
+                        .entries()
+                        .all(|(_, _, bindings)| !bindings.is_empty()));
+                    for &(context, action) in &[
+                        (KeymapContext::Menu, KeyAction::MenuSelect),
+                        (KeymapContext::Menu, KeyAction::Cancel),
+                        (KeymapContext::Game, KeyAction::SaveGame),
+                    ] {
+                        assert_ne!(keymap.describe(context, action), "");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn config_round_trip() {
+        let mut keymap = Keymap::default();
+        keymap.rebind(
+            KeymapContext::Game,
+            KeyAction::SaveGame,
+            "Ctrl+Alt+Shift+S".parse().unwrap(),
+        );
+
+        let mut loaded = Keymap::default();
+        assert!(loaded.apply_config(&keymap.to_config()).is_empty());
+        assert_eq!(loaded, keymap);
+
+        let errors = loaded.apply_config(
+            "# comment\nGame.Fly = F\nMenu.PickUp = G\nGame.PickUp = Hyper+G\nGame.PickUp\nGame.SaveGame =",
+        );
+        assert_eq!(errors.len(), 5);
+    }
+}
@@ -46,4 +46,8 @@ impl Layout {
     pub fn hunger_status(&self, length: i32) -> Point {
         Point::new(self.width - length - 1, self.panel().y1 - 1)
     }
+
+    pub fn encumbrance_status(&self) -> Point {
+        Point::new(1, self.panel().y1 - 1)
+    }
 }
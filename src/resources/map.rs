@@ -11,6 +11,7 @@ use legion::Entity;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 use type_uuid::TypeUuid;
 
 use crate::{
@@ -18,11 +19,106 @@ use crate::{
     util::vector::{Heading, Vector},
 };
 
-#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, EnumIter)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    ShallowWater,
+    DeepWater,
+    Lava,
+    Bridge,
+    Rubble,
+    Grass,
+    Chasm,
+}
+
+/// The cheapest any tile can be to step on. Keeps the A* heuristic from overestimating.
+pub const MIN_PATHING_COST: f32 = 1.0;
+
+impl TileType {
+    #[must_use]
+    pub fn is_walkable(self) -> bool {
+        match self {
+            TileType::Floor
+            | TileType::DownStairs
+            | TileType::ShallowWater
+            | TileType::Bridge
+            | TileType::Rubble
+            | TileType::Grass => true,
+            TileType::Wall | TileType::DeepWater | TileType::Lava | TileType::Chasm => false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_opaque(self) -> bool {
+        self == TileType::Wall
+    }
+
+    /// How much it costs to step onto the tile, relative to plain floor
+    #[must_use]
+    pub fn pathing_cost(self) -> f32 {
+        match self {
+            TileType::ShallowWater => 2.0,
+            TileType::Rubble => 1.5,
+            _ => MIN_PATHING_COST,
+        }
+    }
+
+    /// Walls are special, see `Map::wall_glyph`
+    #[must_use]
+    pub fn glyph(self) -> FontCharType {
+        to_cp437(match self {
+            TileType::Wall => '#',
+            TileType::Floor => '.',
+            TileType::DownStairs => '>',
+            TileType::ShallowWater => '~',
+            TileType::DeepWater | TileType::Lava => '≈',
+            TileType::Bridge => '=',
+            TileType::Rubble => ',',
+            TileType::Grass => '"',
+            TileType::Chasm => '░',
+        })
+    }
+
+    /// Stands for the tile in map diagrams. Same as the glyph, except that lava gets a symbol
+    /// of its own.
+    #[must_use]
+    pub fn symbol(self) -> char {
+        match self {
+            TileType::Wall => '#',
+            TileType::Floor => '.',
+            TileType::DownStairs => '>',
+            TileType::ShallowWater => '~',
+            TileType::DeepWater => '≈',
+            TileType::Lava => '&',
+            TileType::Bridge => '=',
+            TileType::Rubble => ',',
+            TileType::Grass => '"',
+            TileType::Chasm => '░',
+        }
+    }
+
+    #[must_use]
+    pub fn from_symbol(symbol: char) -> Option<TileType> {
+        TileType::iter().find(|tile| tile.symbol() == symbol)
+    }
+
+    #[must_use]
+    pub fn color(self) -> RGB {
+        match self {
+            TileType::Wall => RGB::named(GREEN),
+            TileType::Floor => RGB::named(GRAY50),
+            TileType::DownStairs => RGB::named(CYAN),
+            TileType::ShallowWater => RGB::from_f32(0.4, 0.7, 1.0),
+            TileType::DeepWater => RGB::from_f32(0.1, 0.2, 0.9),
+            TileType::Lava => RGB::from_f32(1.0, 0.35, 0.0),
+            TileType::Bridge => RGB::named(CHOCOLATE),
+            TileType::Rubble => RGB::from_f32(0.6, 0.55, 0.5),
+            TileType::Grass => RGB::from_f32(0.3, 0.8, 0.3),
+            TileType::Chasm => RGB::from_f32(0.2, 0.2, 0.3),
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, TypeUuid, PartialEq, Debug)]
@@ -38,6 +134,10 @@ pub struct Map {
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
     tile_content: HashMap<Position, Vec<Entity>>,
+
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    closed_doors: HashSet<Position>,
 }
 
 impl Map {
@@ -51,6 +151,7 @@ impl Map {
             blocked: HashSet::new(),
             bloodstains: HashSet::new(),
             tile_content: HashMap::new(),
+            closed_doors: HashSet::new(),
         }
     }
 
@@ -105,8 +206,11 @@ impl Map {
         self.blocked.insert(position);
     }
 
+    /// Closed doors are valid exits: whoever paths through them can open them on the way
     fn is_exit_valid(&self, position: Position) -> bool {
-        self.contains(position) && !self.is_blocked(position)
+        self.contains(position)
+            && self[&position].is_walkable()
+            && (!self.is_blocked(position) || self.is_door_closed(position))
     }
 
     pub fn populate_blocked(&mut self) {
@@ -114,7 +218,7 @@ impl Map {
             .tiles
             .iter()
             .enumerate()
-            .filter(|(_i, tile)| **tile == TileType::Wall)
+            .filter(|(_i, tile)| !tile.is_walkable())
             .map(|(i, _tile)| self.idx_pos(i))
             .collect();
     }
@@ -123,6 +227,21 @@ impl Map {
         for content in self.tile_content.values_mut() {
             content.clear();
         }
+        self.closed_doors.clear();
+    }
+
+    pub fn close_door(&mut self, position: Position) {
+        self.closed_doors.insert(position);
+        self.blocked.insert(position);
+    }
+
+    pub fn open_door(&mut self, position: Position) {
+        self.closed_doors.remove(&position);
+        self.blocked.remove(&position);
+    }
+
+    pub fn is_door_closed(&self, position: Position) -> bool {
+        self.closed_doors.contains(&position)
     }
 
     pub fn add_tile_content(&mut self, position: Position, entity: Entity) {
@@ -154,7 +273,7 @@ impl Map {
             if !self.contains(position) {
                 return false;
             }
-            self[&position] == TileType::Wall && revealed_tiles.contains(&position)
+            self[&position].is_opaque() && revealed_tiles.contains(&position)
         };
 
         if is_revealed_and_wall(x, y - 1) {
@@ -218,6 +337,72 @@ impl Map {
             .map(|p| Position::from(*p))
             .collect()
     }
+
+    /// Reads a map diagram: one line per row, one `TileType::symbol` per tile. Whitespace around
+    /// lines and blank lines are ignored, so diagrams can be indented inline in tests.
+    ///
+    /// # Panics
+    ///
+    /// If the rows aren't all the same width
+    #[must_use]
+    pub fn from_ascii(diagram: &str) -> Map {
+        Map::from_ascii_with_markers(diagram).0
+    }
+
+    /// Like `from_ascii`, but any character that isn't a tile symbol is an entity marker: the
+    /// tile under it is floor, and the marker is returned by position.
+    #[must_use]
+    pub fn from_ascii_with_markers(diagram: &str) -> (Map, HashMap<Position, char>) {
+        let rows: Vec<Vec<char>> = diagram
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().collect())
+            .collect();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "Rows of a map diagram must all be the same width"
+        );
+
+        let mut map = Map::new(width as i32, rows.len() as i32, 1);
+        let mut markers = HashMap::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, symbol) in row.iter().enumerate() {
+                let position = Position::new(x as i32, y as i32);
+                map[&position] = TileType::from_symbol(*symbol).unwrap_or_else(|| {
+                    markers.insert(position, *symbol);
+                    TileType::Floor
+                });
+            }
+        }
+        (map, markers)
+    }
+
+    #[must_use]
+    pub fn to_ascii(&self) -> String {
+        self.to_ascii_with_markers(&HashMap::new())
+    }
+
+    /// Markers are drawn over whatever tile they're on, so only markers on floor survive a round
+    /// trip through `from_ascii_with_markers`.
+    #[must_use]
+    pub fn to_ascii_with_markers(&self, markers: &HashMap<Position, char>) -> String {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let position = Position::new(x, y);
+                        markers
+                            .get(&position)
+                            .copied()
+                            .unwrap_or_else(|| self[&position].symbol())
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 impl Index<&Position> for Map {
@@ -298,7 +483,8 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx: usize) -> bool {
-        idx < self.tiles.len() && self.tiles[idx as usize] == TileType::Wall
+        idx < self.tiles.len()
+            && (self.tiles[idx as usize].is_opaque() || self.is_door_closed(self.idx_pos(idx)))
     }
 
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
@@ -312,22 +498,29 @@ impl BaseMap for Map {
             })
             .map(|vector| position + vector)
             .filter(|candidate| self.is_exit_valid(*candidate))
-            .map(|exit| (self.pos_idx(exit), position.distance(exit)))
+            .map(|exit| {
+                // Opening a door takes a turn
+                let door_cost = if self.is_door_closed(exit) { 1.0 } else { 0.0 };
+                let cost = position.distance(exit) * self[&exit].pathing_cost() + door_cost;
+                (self.pos_idx(exit), cost)
+            })
             .collect()
     }
 
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
         let p1 = self.idx_pos(idx1);
         let p2 = self.idx_pos(idx2);
-        DistanceAlg::Pythagoras.distance2d(*p1, *p2)
+        DistanceAlg::Pythagoras.distance2d(*p1, *p2) * MIN_PATHING_COST
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use bracket_lib::prelude::BaseMap;
+
     use crate::{
         components::position::Position,
-        resources::map::{Map, MapIterator},
+        resources::map::{Map, MapIterator, TileType},
     };
 
     #[test]
@@ -354,4 +547,69 @@ mod tests {
             assert_eq!(iter.size_hint().1.unwrap(), expected);
         }
     }
+
+    #[test]
+    fn closed_doors_block_movement_and_sight() {
+        let mut map = Map::new(5, 5, 1);
+        for (x, y) in vec![(1, 2), (2, 2), (3, 2)] {
+            map[(x, y)] = TileType::Floor;
+        }
+        let door = Position::new(2, 2);
+        let door_idx = map.pos_idx(door);
+
+        map.close_door(door);
+        assert!(map.is_blocked(door));
+        assert!(map.is_opaque(door_idx));
+        // Pathing still goes through closed doors
+        assert!(map
+            .get_available_exits(map.pos_idx(Position::new(1, 2)))
+            .iter()
+            .any(|(idx, _)| *idx == door_idx));
+
+        map.open_door(door);
+        assert!(!map.is_blocked(door));
+        assert!(!map.is_opaque(door_idx));
+    }
+
+    #[test]
+    fn terrain_affects_pathing() {
+        let mut map = Map::from_ascii(
+            "
+            #####
+            #.~≈#
+            #####
+            ",
+        );
+        map.populate_blocked();
+
+        let exits = map.get_available_exits(map.pos_idx(Position::new(2, 1)));
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].0, map.pos_idx(Position::new(1, 1)));
+
+        let exits = map.get_available_exits(map.pos_idx(Position::new(1, 1)));
+        assert_eq!(
+            exits.to_vec(),
+            vec![(map.pos_idx(Position::new(2, 1)), 2.0)]
+        );
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let diagram = "\
+#########
+#@..g..>#
+#~~=~≈≈&#
+#,,\"\"░░!#
+#########";
+        let (map, markers) = Map::from_ascii_with_markers(diagram);
+        assert_eq!((map.width, map.height), (9, 5));
+        assert_eq!(map[(5, 2)], TileType::DeepWater);
+        assert_eq!(map[(7, 2)], TileType::Lava);
+        assert_eq!(map[(1, 1)], TileType::Floor);
+        assert_eq!(markers.len(), 3);
+        assert_eq!(markers[&Position::new(4, 1)], 'g');
+
+        assert_eq!(map.to_ascii_with_markers(&markers), diagram);
+        assert_eq!(Map::from_ascii(&map.to_ascii()).to_ascii(), map.to_ascii());
+    }
 }
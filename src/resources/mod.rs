@@ -1,17 +1,27 @@
+pub use autopilot::*;
+pub use clock::*;
 pub use frame_data::*;
 pub use gamelog::*;
 pub use input::Input;
+pub use item_knowledge::*;
+pub use keymap::*;
 pub use layout::*;
 pub use map::*;
 pub use rex_assets::*;
 pub use runstate::*;
 pub use shown_inventory::*;
+pub use statistics::*;
 
+pub mod autopilot;
+pub mod clock;
 pub mod frame_data;
 pub mod gamelog;
 pub mod input;
+pub mod item_knowledge;
+pub mod keymap;
 pub mod layout;
 pub mod map;
 pub mod rex_assets;
 pub mod runstate;
 pub mod shown_inventory;
+pub mod statistics;
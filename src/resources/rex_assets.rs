@@ -1,23 +1,70 @@
+use std::collections::HashMap;
+
 use bracket_lib::prelude::{embedded_resource, link_resource, XpFile, EMBED};
 
 macro_rules! asset_path {
     (SMALL_DUNGEON) => {
         "../../assets/SmallDungeon_80x50.xp"
     };
+    (VAULT_TREASURE) => {
+        "../../assets/prefabs/vault_treasure.xp"
+    };
+    (VAULT_GOBLIN_CAMP) => {
+        "../../assets/prefabs/vault_goblin_camp.xp"
+    };
+    (VAULT_TRAP_HALL) => {
+        "../../assets/prefabs/vault_trap_hall.xp"
+    };
+    (VAULT_SHRINE) => {
+        "../../assets/prefabs/vault_shrine.xp"
+    };
+    (LEVEL_ORC_OUTPOST) => {
+        "../../assets/prefabs/level_orc_outpost.xp"
+    };
 }
 
 embedded_resource!(SMALL_DUNGEON, asset_path!(SMALL_DUNGEON));
+embedded_resource!(VAULT_TREASURE, asset_path!(VAULT_TREASURE));
+embedded_resource!(VAULT_GOBLIN_CAMP, asset_path!(VAULT_GOBLIN_CAMP));
+embedded_resource!(VAULT_TRAP_HALL, asset_path!(VAULT_TRAP_HALL));
+embedded_resource!(VAULT_SHRINE, asset_path!(VAULT_SHRINE));
+embedded_resource!(LEVEL_ORC_OUTPOST, asset_path!(LEVEL_ORC_OUTPOST));
+
+/// Depth at which the orc outpost replaces the generated level
+pub const ORC_OUTPOST_DEPTH: i32 = 5;
 
 pub struct RexAssets {
     pub menu: XpFile,
+    /// Rooms stamped into generated levels wherever they fit, see `mapgen::prefab`
+    pub vaults: Vec<XpFile>,
+    /// Hand-authored levels, keyed by the depth they replace
+    pub levels: HashMap<i32, XpFile>,
 }
 
 impl RexAssets {
     pub fn new() -> RexAssets {
         link_resource!(SMALL_DUNGEON, asset_path!(SMALL_DUNGEON));
+        link_resource!(VAULT_TREASURE, asset_path!(VAULT_TREASURE));
+        link_resource!(VAULT_GOBLIN_CAMP, asset_path!(VAULT_GOBLIN_CAMP));
+        link_resource!(VAULT_TRAP_HALL, asset_path!(VAULT_TRAP_HALL));
+        link_resource!(VAULT_SHRINE, asset_path!(VAULT_SHRINE));
+        link_resource!(LEVEL_ORC_OUTPOST, asset_path!(LEVEL_ORC_OUTPOST));
+
+        let mut levels = HashMap::new();
+        levels.insert(
+            ORC_OUTPOST_DEPTH,
+            XpFile::from_resource(asset_path!(LEVEL_ORC_OUTPOST)).unwrap(),
+        );
 
         RexAssets {
             menu: XpFile::from_resource(asset_path!(SMALL_DUNGEON)).unwrap(),
+            vaults: vec![
+                XpFile::from_resource(asset_path!(VAULT_TREASURE)).unwrap(),
+                XpFile::from_resource(asset_path!(VAULT_GOBLIN_CAMP)).unwrap(),
+                XpFile::from_resource(asset_path!(VAULT_TRAP_HALL)).unwrap(),
+                XpFile::from_resource(asset_path!(VAULT_SHRINE)).unwrap(),
+            ],
+            levels,
         }
     }
 }
@@ -1,13 +1,16 @@
 use std::collections::VecDeque;
 use std::fmt;
 
+use bracket_lib::prelude::Point;
 use legion::Entity;
 use macro_attr::*;
 use newtype_derive::*;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+use crate::components::Position;
 use crate::resources::Map;
+use crate::util::i18n::tr;
 use crate::util::saveload;
 
 #[derive(PartialEq, Copy, Clone, Debug, EnumIter)]
@@ -47,16 +50,32 @@ impl fmt::Display for MainMenuSelection {
         write!(
             f,
             "{}",
-            match self {
-                NewGame => "Begin New Game",
-                LoadGame => "Load Game",
+            tr(match self {
+                NewGame => "menu.new_game",
+                LoadGame => "menu.load_game",
                 #[cfg(not(target_arch = "wasm32"))]
-                Quit => "Quit",
-            }
+                Quit => "menu.quit",
+            })
         )
     }
 }
 
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum VendorMode {
+    Buy,
+    Sell,
+}
+
+impl VendorMode {
+    #[must_use]
+    pub fn switch(self) -> VendorMode {
+        match self {
+            VendorMode::Buy => VendorMode::Sell,
+            VendorMode::Sell => VendorMode::Buy,
+        }
+    }
+}
+
 #[derive(PartialEq, Clone, Debug)]
 pub enum RunState {
     AwaitingInput,
@@ -69,6 +88,30 @@ pub enum RunState {
     ShowTargeting {
         range: i32,
         item: Entity,
+        cursor: Position,
+        /// Where the mouse was last seen, so that moving it moves the cursor too
+        mouse: Point,
+    },
+    ShowLog {
+        /// How many entries up from the most recent one
+        scroll: usize,
+    },
+    ShowCharacter,
+    ShowLook {
+        cursor: Position,
+    },
+    ShowKeymap {
+        selection: usize,
+        /// Waiting for the new key for the selected action
+        rebinding: bool,
+    },
+    ShowVendor {
+        vendor: Entity,
+        mode: VendorMode,
+    },
+    ResolveTrade {
+        vendor: Entity,
+        mode: VendorMode,
     },
     MainMenu {
         selection: MainMenuSelection,
@@ -78,6 +121,7 @@ pub enum RunState {
     LoadGame,
     NextLevel,
     GameOver,
+    Victory,
     MagicMapReveal {
         row: i32,
     },
@@ -95,6 +139,7 @@ impl RunState {
         *self == RunState::ShowDropItem
             || *self == RunState::ShowInventory
             || *self == RunState::ShowRemoveItem
+            || matches!(self, RunState::ShowVendor { .. })
     }
 
     pub fn main_menu_item_enabled(&self, item: MainMenuSelection) -> bool {
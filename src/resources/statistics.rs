@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use type_uuid::TypeUuid;
+
+/// Tallies of the run so far, for the character sheet
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "5f0c1e52-8d7b-4a6e-9c33-2b1e4f7a9d60"]
+pub struct Statistics {
+    /// Monsters that died during the player's turn
+    pub kills: u32,
+}
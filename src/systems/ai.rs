@@ -72,12 +72,17 @@ pub fn ai(
                 map,
             );
             if path.success && path.steps.len() > 1 {
-                cae.add_effect(
-                    cause,
-                    Label::MoveIntent {
-                        target_position: map.idx_pos(path.steps[1]),
-                    },
-                );
+                let target_position = map.idx_pos(path.steps[1]);
+                if map.is_door_closed(target_position) {
+                    cae.add_effect(
+                        cause,
+                        Label::OpenDoorIntent {
+                            position: target_position,
+                        },
+                    );
+                } else {
+                    cae.add_effect(cause, Label::MoveIntent { target_position });
+                }
             }
         } else {
             cae.add_effect(cause, Label::SkipBecauseHidden);
@@ -0,0 +1,23 @@
+use crate::systems::prelude::*;
+
+cae_system_state!(AutopilotSystemState { subscribe(Damage, Spotted) });
+
+/// Hands control back to the player when they get hurt or notice something hidden
+#[system]
+#[read_component(Player)]
+pub fn autopilot(
+    #[state] state: &AutopilotSystemState,
+    #[resource] cae: &mut CauseAndEffect,
+    #[resource] autopilot: &mut Autopilot,
+    world: &SubWorld,
+) {
+    for damage in cae.get_queue(state.damage) {
+        extract_label!(damage @ Damage => to);
+        if world.is_player(to) {
+            *autopilot = Autopilot::Off;
+        }
+    }
+    if !cae.get_queue(state.spotted).is_empty() {
+        *autopilot = Autopilot::Off;
+    }
+}
@@ -5,19 +5,74 @@ cae_system_state!(DeathSystemState { subscribe(Death) });
 #[system]
 #[read_component(Name)]
 #[read_component(Player)]
+#[read_component(Monster)]
+#[read_component(Boss)]
+#[read_component(Position)]
+#[allow(clippy::too_many_arguments)]
 pub fn death(
     #[state] state: &DeathSystemState,
     #[resource] run_state_queue: &mut RunStateQueue,
     #[resource] cae: &mut CauseAndEffect,
     #[resource] deferred_cleanup: &mut DeferredCleanup,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] map: &Map,
+    #[resource] statistics: &mut Statistics,
     world: &SubWorld,
+    commands: &mut CommandBuffer,
 ) {
     for death in cae.get_queue(state.death) {
         extract_label!(death @ Death => entity);
         if world.is_player(entity) {
             run_state_queue.push_back(RunState::GameOver);
         } else {
+            let turn =
+                cae.find_nearest_ancestor(&death, |link| matches!(link.label, Label::Turn { .. }));
+            if let Some(Link {
+                label: Label::Turn { actor },
+                ..
+            }) = turn
+            {
+                if world.is_player(actor) && world.has_component::<Monster>(entity) {
+                    statistics.kills += 1;
+                }
+            }
+            if world.has_component::<Boss>(entity) {
+                run_state_queue.push_back(RunState::Victory);
+            }
+            if world.has_component::<Monster>(entity) && rng.roll_dice(1, 2) == 1 {
+                let amount = rng.roll_dice(1, 6) + map.depth * 2;
+                let pile = crate::mapgen::spawner::gold_pile(commands, amount);
+                commands.add_component(pile, world.get_component::<Position>(entity));
+            }
             deferred_cleanup.entity(entity);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::death::{death_system, DeathSystemState};
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    #[test]
+    fn only_monsters_killed_by_the_player_count() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let goblin = test.world.push((Monster, Position::new(6, 5)));
+        let orc = test.world.push((Monster, Position::new(7, 5)));
+        let barrel = test.world.push((Position::new(5, 6),));
+        let state = DeathSystemState::new(&test.resources);
+
+        let turn = test.turn(player);
+        test.cause(&turn, Label::Death { entity: goblin });
+        test.cause(&turn, Label::Death { entity: barrel });
+        let turn = test.turn(goblin);
+        test.cause(&turn, Label::Death { entity: orc });
+        test.run(|schedule| schedule.add_system(death_system(state)));
+
+        assert_eq!(test.resources.get::<Statistics>().unwrap().kills, 1);
+        assert!(!test.world.contains(goblin));
+        assert!(!test.world.contains(orc));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::systems::prelude::*;
+
+cae_system_state!(DoorSystemState {
+    subscribe(OpenDoorIntent, CloseDoorIntent)
+});
+
+#[system]
+#[read_component(Entity)]
+#[read_component(Position)]
+#[read_component(Door)]
+#[read_component(Renderable)]
+#[write_component(Viewshed)]
+pub fn door(
+    #[state] state: &DoorSystemState,
+    #[resource] cae: &mut CauseAndEffect,
+    #[resource] map: &mut Map,
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+) {
+    let mut changed = false;
+
+    for intent in cae.get_queue(state.open_door_intent) {
+        extract_label!(intent @ OpenDoorIntent => position);
+        if let Some(door) = door_at(world, map, position, false) {
+            set_door(world, commands, door, true);
+            map.open_door(position);
+            cae.add_effect(&intent, Label::DoorOpened { door });
+            changed = true;
+        } else {
+            cae.add_effect(&intent, Label::MovementBlocked);
+        }
+    }
+
+    for intent in cae.get_queue(state.close_door_intent) {
+        extract_nearest_ancestor!(cae, intent @ Turn => actor);
+        let actor_position = world.get_component::<Position>(actor);
+
+        // Just the one, even if there are more doors around
+        if let Some((door, position)) = closable_door(world, map, actor_position) {
+            set_door(world, commands, door, false);
+            map.close_door(position);
+            cae.add_effect(&intent, Label::DoorClosed { door });
+            changed = true;
+        } else {
+            cae.add_effect(&intent, Label::NoDoorToClose);
+        }
+    }
+
+    // Whatever was seen through the door (or wasn't) is now different
+    if changed {
+        <(&mut Viewshed,)>::query().for_each_mut(world, |(viewshed,)| {
+            viewshed.dirty = true;
+        });
+    }
+}
+
+fn door_at(world: &SubWorld, map: &Map, position: Position, open: bool) -> Option<Entity> {
+    map.get_tile_contents(position)?
+        .iter()
+        .find(|&&entity| {
+            <(&Door,)>::query()
+                .get(world, entity)
+                .map_or(false, |(door,)| door.open == open)
+        })
+        .copied()
+}
+
+/// An open door next to `around` with nothing stuck in it
+fn closable_door(world: &SubWorld, map: &Map, around: Position) -> Option<(Entity, Position)> {
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let position = around + Vector::new(dx, dy);
+            if map.get_tile_contents(position).map_or(0, Vec::len) != 1 {
+                continue;
+            }
+            if let Some(door) = door_at(world, map, position, true) {
+                return Some((door, position));
+            }
+        }
+    }
+    None
+}
+
+fn set_door(world: &SubWorld, commands: &mut CommandBuffer, entity: Entity, open: bool) {
+    let door = Door::new(open);
+    let mut renderable = world.get_component::<Renderable>(entity);
+    renderable.glyph = door.glyph();
+    commands.add_component(entity, door);
+    commands.add_component(entity, renderable);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::door::{door_system, DoorSystemState};
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    fn door(test: &mut TestWorld, position: Position, open: bool) -> Entity {
+        let door = Door::new(open);
+        test.world.push((
+            door,
+            position,
+            Renderable {
+                glyph: door.glyph(),
+                color: ColorPair::new(WHITE, BLACK),
+                render_order: RenderOrder::Items,
+            },
+        ))
+    }
+
+    /// A player who has already looked around
+    fn player(test: &mut TestWorld) -> Entity {
+        let player = test.player(Position::new(5, 5));
+        test.world
+            .entry(player)
+            .unwrap()
+            .get_component_mut::<Viewshed>()
+            .unwrap()
+            .dirty = false;
+        player
+    }
+
+    fn act(test: &mut TestWorld, player: Entity, label: Label) -> Vec<Label> {
+        let state = DoorSystemState::new(&test.resources);
+        let turn = test.turn(player);
+        let intent = test.cause(&turn, label);
+        test.run(|schedule| schedule.add_system(door_system(state)));
+        test.effects(&intent)
+    }
+
+    #[test]
+    fn bumping_into_a_door_opens_it() {
+        let mut test = TestWorld::default();
+        let player = player(&mut test);
+        let position = Position::new(6, 5);
+        let door = door(&mut test, position, false);
+
+        let effects = act(&mut test, player, Label::OpenDoorIntent { position });
+
+        assert_eq!(effects, vec![Label::DoorOpened { door }]);
+        assert_eq!(test.get_component::<Door>(door), Door::new(true));
+        assert_eq!(
+            test.get_component::<Renderable>(door).glyph,
+            Door::new(true).glyph()
+        );
+        assert!(!test
+            .resources
+            .get::<Map>()
+            .unwrap()
+            .is_door_closed(position));
+        assert!(test.get_component::<Viewshed>(player).dirty);
+    }
+
+    #[test]
+    fn bumping_into_anything_else_is_blocked() {
+        let mut test = TestWorld::default();
+        let player = player(&mut test);
+
+        let effects = act(
+            &mut test,
+            player,
+            Label::OpenDoorIntent {
+                position: Position::new(4, 5),
+            },
+        );
+
+        assert_eq!(effects, vec![Label::MovementBlocked]);
+        assert!(!test.get_component::<Viewshed>(player).dirty);
+    }
+
+    #[test]
+    fn doors_with_something_in_the_way_stay_open() {
+        let mut test = TestWorld::default();
+        let player = player(&mut test);
+        let position = Position::new(6, 5);
+        let door = door(&mut test, position, true);
+        let rock = test.world.push((Item, position));
+
+        let effects = act(&mut test, player, Label::CloseDoorIntent);
+        assert_eq!(effects, vec![Label::NoDoorToClose]);
+        assert_eq!(test.get_component::<Door>(door), Door::new(true));
+        assert!(!test.get_component::<Viewshed>(player).dirty);
+
+        test.world.remove(rock);
+        let effects = act(&mut test, player, Label::CloseDoorIntent);
+        assert_eq!(effects, vec![Label::DoorClosed { door }]);
+        assert_eq!(test.get_component::<Door>(door), Door::new(false));
+        assert!(test
+            .resources
+            .get::<Map>()
+            .unwrap()
+            .is_door_closed(position));
+        assert!(test.get_component::<Viewshed>(player).dirty);
+    }
+
+    #[test]
+    fn one_door_is_closed_at_a_time() {
+        let mut test = TestWorld::default();
+        let player = player(&mut test);
+        let doors = [
+            door(&mut test, Position::new(6, 5), true),
+            door(&mut test, Position::new(4, 5), true),
+        ];
+
+        let effects = act(&mut test, player, Label::CloseDoorIntent);
+
+        assert_eq!(effects.len(), 1);
+        let closed = doors
+            .iter()
+            .filter(|door| !test.get_component::<Door>(**door).open)
+            .count();
+        assert_eq!(closed, 1);
+    }
+}
@@ -5,11 +5,15 @@ cae_system_state!(GameLogSystemState {
         Ate, NoLongerWellFed, Hungry, Starving,
         Damage, Healing, Death,
         Confused, ConfusionOver,
-        PickupNothingHere, PickupDone, DropDone,
+        PickupNothingHere, PickupDone, PickupBlocked, DropDone,
         EquipDone, RemoveDone, NoValidTargets, TooFarAway,
         NoStairsHere, MovedToNextLevel,
-        MagicMapping, Spotted,
-        EntryTriggered,
+        DoorOpened, DoorClosed, NoDoorToClose,
+        NothingToExplore, NoKnownStairs,
+        MagicMapping, Spotted, NothingFound, CannotRest,
+        EntryTriggered, ItemIdentified,
+        CannotRemoveCursed, CurseRemoved,
+        BuyDone, SellDone, CannotAfford, PurchaseBlocked,
     )
 });
 
@@ -17,13 +21,21 @@ cae_system_state!(GameLogSystemState {
 #[read_component(Player)]
 #[read_component(Name)]
 #[read_component(Confusion)]
+#[read_component(Enchantment)]
+#[read_component(Gold)]
 pub fn game_log(
     #[state] state: &GameLogSystemState,
     #[resource] game_log: &mut GameLog,
     #[resource] cae: &mut CauseAndEffect,
+    #[resource] item_knowledge: &ItemKnowledge,
+    #[resource] clock: &Clock,
     world: &SubWorld,
 ) {
+    type Handler =
+        fn(&GameLogSystemState, &mut CauseAndEffect, &SubWorld, &ItemKnowledge) -> Vec<LogRecord>;
+
     for f in &[
+        item_identified as Handler,
         ate,
         no_longer_well_fed,
         hungry,
@@ -35,314 +47,378 @@ pub fn game_log(
         confusion_over,
         pickup_nothing_here,
         pickup_done,
+        pickup_blocked,
         drop_done,
         remove_done,
+        cannot_remove_cursed,
+        curse_removed,
         equip_done,
         too_far_away,
         no_valid_targets,
         no_stairs_here,
         moved_to_next_level,
+        door_opened,
+        door_closed,
+        no_door_to_close,
+        nothing_to_explore,
+        no_known_stairs,
         magic_mapping,
         entry_triggered,
         spotted,
+        nothing_found,
+        cannot_rest,
+        buy_done,
+        sell_done,
+        cannot_afford,
+        purchase_blocked,
     ] {
-        for msg in f(state, cae, world) {
-            game_log.push(msg);
+        for record in f(state, cae, world, item_knowledge) {
+            game_log.push(clock.turn, record);
+        }
+    }
+}
+
+/// The kinds of labels that led to `link`, outermost first and without the root
+fn cause_chain(cae: &CauseAndEffect, link: &Link) -> Vec<String> {
+    let mut chain = vec![];
+    let mut current = Some(*link);
+    while let Some(link) = current {
+        if link.label != Label::Root {
+            chain.push(<&str>::from(&link.label).to_string());
         }
+        current = cae.get_cause(&link);
+    }
+    chain.reverse();
+    chain
+}
+
+fn log_entity(world: &SubWorld, entity: Entity) -> LogEntity {
+    LogEntity {
+        name: world.get_component::<Name>(entity).to_string(),
+        is_player: world.is_player(entity),
+    }
+}
+
+/// The item as the player knows it right now
+fn log_item(world: &SubWorld, knowledge: &ItemKnowledge, item: Entity) -> LogItem {
+    let entry = world.entry_ref(item).unwrap();
+    let name = entry.get_component::<Name>().unwrap();
+    LogItem {
+        name: name.to_string(),
+        appearance: if knowledge.is_identified(name) {
+            None
+        } else {
+            knowledge.appearance(name).cloned()
+        },
+        enchantment: entry
+            .get_component::<Enchantment>()
+            .map_or(0, |enchantment| enchantment.level),
     }
 }
 
 macro_rules! handle_event {
-    ($queue:ident, |$state:ident, $cae:ident, $world:ident, $event:ident| $body:expr ) => {
+    ($queue:ident, |$state:ident, $cae:ident, $world:ident, $knowledge:ident, $event:ident| $body:expr ) => {
+        #[allow(unused_variables, clippy::redundant_closure_call)]
         fn $queue(
             $state: &GameLogSystemState,
             $cae: &mut CauseAndEffect,
             $world: &SubWorld,
-        ) -> Vec<String> {
+            $knowledge: &ItemKnowledge,
+        ) -> Vec<LogRecord> {
             $cae.get_queue($state.$queue)
                 .iter()
-                .flat_map(|$event| $body)
+                .flat_map(|$event| {
+                    let record: Option<LogRecord> = (|| $body)();
+                    record.map(|record| record.caused_by(cause_chain($cae, $event)))
+                })
                 .collect()
         }
     };
 }
 
-handle_event!(no_longer_well_fed, |state, cae, world, event| {
+handle_event!(no_longer_well_fed, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     if !world.is_player(actor) {
         return None;
     }
-    Some("You are no longer well fed.".to_string())
+    Some(LogRecord::new(LogKind::NoLongerWellFed))
 });
 
-handle_event!(hungry, |state, cae, world, event| {
+handle_event!(hungry, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     if !world.is_player(actor) {
         return None;
     }
-    Some("You are hungry.".to_string())
+    Some(LogRecord::new(LogKind::Hungry))
 });
 
-handle_event!(starving, |state, cae, world, event| {
+handle_event!(starving, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     if !world.is_player(actor) {
         return None;
     }
-    Some("You are starving!".to_string())
+    Some(LogRecord::new(LogKind::Starving))
 });
 
-handle_event!(ate, |state, cae, world, event| {
+handle_event!(ate, |state, cae, world, knowledge, event| {
     extract_label!(event @ Ate => who, what);
     if !world.is_player(who) {
         return None;
     }
-    Some(format!(
-        "You eat the {}.",
-        world.get_component::<Name>(what)
-    ))
+    Some(
+        LogRecord::new(LogKind::Ate)
+            .actor(log_entity(world, who))
+            .item(log_item(world, knowledge, what)),
+    )
 });
 
-handle_event!(damage, |state, cae, world, damage| {
+handle_event!(damage, |state, cae, world, knowledge, damage| {
     extract_label!(damage @ Damage => to, amount);
     extract_nearest_ancestor!(cae, damage @ Turn => actor);
     match cae.get_cause(&damage).map(|link| link.label).unwrap() {
-        Label::HungerPang => {
-            if world.is_player(to) {
-                Some(format!(
-                    "Your hunger pangs are getting painful! You suffer {} hp damage.",
-                    amount
-                ))
-            } else {
-                Some(format!(
-                    "The stomach of {} grumbles loudly.",
-                    world.get_component::<Name>(to)
-                ))
-            }
-        }
+        Label::HungerPang => Some(
+            LogRecord::new(LogKind::HungerPang)
+                .target(log_entity(world, to))
+                .amount(amount),
+        ),
         Label::UseOnTarget {
             item,
             target: use_target,
         } => {
             assert_eq!(use_target, to);
             assert!(world.is_player(actor));
-            Some(format!(
-                "You use {} on {}, inflicting {} hp of damage.",
-                world.get_component::<Name>(item),
-                world.get_component::<Name>(to),
-                amount
-            ))
+            Some(
+                LogRecord::new(LogKind::ItemDamage)
+                    .actor(log_entity(world, actor))
+                    .target(log_entity(world, to))
+                    .item(log_item(world, knowledge, item))
+                    .amount(amount),
+            )
         }
         Label::Hit => {
-            let actor_name = world.get_component::<Name>(actor);
-            let target_name = if world.is_player(to) {
-                "you".to_string()
+            let kind = if amount <= 0 {
+                LogKind::Miss
             } else {
-                world.get_component::<Name>(to).into()
+                LogKind::Hit
             };
-            if world.is_player(actor) {
-                if amount <= 0 {
-                    Some(format!("You are unable to hurt {}.", target_name))
-                } else {
-                    Some(format!("You hit {}, for {} hp.", target_name, amount))
-                }
-            } else if amount <= 0 {
-                Some(format!("{} is unable to hurt {}.", actor_name, target_name))
-            } else {
-                Some(format!(
-                    "{} hits {}, for {} hp.",
-                    actor_name, target_name, amount
-                ))
-            }
-        }
-        Label::EntryTriggered { trigger } => {
-            let (actor_name, trigger_verb) = if world.is_player(actor) {
-                ("You".to_string(), "trigger".to_string())
-            } else {
-                (
-                    world.get_component::<Name>(actor).into(),
-                    "triggers".to_string(),
-                )
-            };
-            Some(format!(
-                "{} {} {}, suffering {} hp damage.",
-                actor_name,
-                trigger_verb,
-                world.get_component::<Name>(trigger),
-                amount
-            ))
+            Some(
+                LogRecord::new(kind)
+                    .actor(log_entity(world, actor))
+                    .target(log_entity(world, to))
+                    .amount(amount),
+            )
         }
+        Label::EntryTriggered { trigger } => Some(
+            LogRecord::new(LogKind::TrapDamage)
+                .actor(log_entity(world, actor))
+                .target(log_entity(world, trigger))
+                .amount(amount),
+        ),
         _ => None,
     }
 });
 
-handle_event!(confusion_over, |state, cae, world, event| {
+handle_event!(confusion_over, |state, cae, world, knowledge, event| {
     extract_label!(event @ ConfusionOver => entity);
     assert!(!world.is_player(entity));
-    Some(format!(
-        "{} is no longer confused!",
-        world.get_component::<Name>(entity)
-    ))
+    Some(LogRecord::new(LogKind::ConfusionOver).target(log_entity(world, entity)))
 });
 
-handle_event!(pickup_nothing_here, |state, cae, world, event| {
+handle_event!(pickup_nothing_here, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     if !world.is_player(actor) {
         return None;
     }
-    Some("There is nothing here to pick up.".to_string())
+    Some(LogRecord::new(LogKind::PickupNothingHere))
 });
 
-handle_event!(pickup_done, |state, cae, world, event| {
+handle_event!(pickup_done, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_cause!(cae, event @ PickupAction => item);
-    let item_name = world.get_component::<Name>(item);
+    if let Ok((gold,)) = <(&Gold,)>::query().get(world, item) {
+        if !world.is_player(actor) {
+            return None;
+        }
+        return Some(
+            LogRecord::new(LogKind::PickupGold)
+                .actor(log_entity(world, actor))
+                .amount(gold.amount),
+        );
+    }
+    Some(
+        LogRecord::new(LogKind::Pickup)
+            .actor(log_entity(world, actor))
+            .item(log_item(world, knowledge, item)),
+    )
+});
 
-    Some(if world.is_player(actor) {
-        format!("You pick up the {}.", item_name)
-    } else {
-        format!(
-            "The {} picks up the {}.",
-            world.get_component::<Name>(actor),
-            item_name
-        )
-    })
+handle_event!(pickup_blocked, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    extract_cause!(cae, event @ PickupAction => item);
+    if !world.is_player(actor) {
+        return None;
+    }
+    Some(
+        LogRecord::new(LogKind::PickupBlocked)
+            .actor(log_entity(world, actor))
+            .item(log_item(world, knowledge, item)),
+    )
 });
 
-handle_event!(drop_done, |state, cae, world, event| {
+handle_event!(drop_done, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_cause!(cae, event @ DropIntent => item);
-    let item_name = world.get_component::<Name>(item);
-
-    Some(if world.is_player(actor) {
-        format!("You drop the {}.", item_name)
-    } else {
-        format!(
-            "The {} drops the {}.",
-            world.get_component::<Name>(actor),
-            item_name
-        )
-    })
+    Some(
+        LogRecord::new(LogKind::Drop)
+            .actor(log_entity(world, actor))
+            .item(log_item(world, knowledge, item)),
+    )
 });
 
-handle_event!(equip_done, |state, cae, world, event| {
+handle_event!(equip_done, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_cause!(cae, event @ UseOnTarget => item, target);
     assert_eq!(actor, target); // This may be removed to allow advanced reverse pickpocketing I guess
-    let item_name = world.get_component::<Name>(item);
-
-    Some(if world.is_player(actor) {
-        format!("You equip {}.", item_name)
-    } else {
-        format!(
-            "The {} equips {}.",
-            world.get_component::<Name>(actor),
-            item_name
-        )
-    })
+    Some(
+        LogRecord::new(LogKind::Equip)
+            .actor(log_entity(world, actor))
+            .item(log_item(world, knowledge, item)),
+    )
 });
 
-handle_event!(remove_done, |state, cae, world, event| {
+handle_event!(remove_done, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_cause!(cae, event @ RemoveIntent => item);
-    let item_name = world.get_component::<Name>(item);
-
-    Some(if world.is_player(actor) {
-        format!("You unequip {}.", item_name)
-    } else {
-        format!(
-            "The {} unequips {}.",
-            world.get_component::<Name>(actor),
-            item_name
-        )
-    })
+    Some(
+        LogRecord::new(LogKind::Unequip)
+            .actor(log_entity(world, actor))
+            .item(log_item(world, knowledge, item)),
+    )
 });
 
-handle_event!(death, |state, cae, world, event| {
+handle_event!(death, |state, cae, world, knowledge, event| {
     extract_label!(event @ Death => entity);
     if world.is_player(entity) {
         return None;
     }
-    Some(format!("{} is dead.", world.get_component::<Name>(entity)))
+    Some(LogRecord::new(LogKind::Death).target(log_entity(world, entity)))
 });
 
-handle_event!(too_far_away, |state, cae, world, event| {
+handle_event!(too_far_away, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_nearest_ancestor!(cae, event @ UseIntent => item);
     if !world.is_player(actor) {
         return None;
     }
-    Some(format!(
-        "That's too far away for {}.",
-        world.get_component::<Name>(item)
-    ))
+    Some(LogRecord::new(LogKind::TooFarAway).item(log_item(world, knowledge, item)))
 });
 
-handle_event!(no_valid_targets, |state, cae, world, event| {
+handle_event!(no_valid_targets, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_nearest_ancestor!(cae, event @ UseIntent => item);
     if !world.is_player(actor) {
         return None;
     }
-    Some(format!(
-        "No valid targets found for {}.",
-        world.get_component::<Name>(item)
-    ))
+    Some(LogRecord::new(LogKind::NoValidTargets).item(log_item(world, knowledge, item)))
 });
 
-handle_event!(healing, |state, cae, world, event| {
+handle_event!(healing, |state, cae, world, knowledge, event| {
     extract_label!(event @ Healing => amount, to);
     extract_cause!(cae, event @ UseOnTarget => item, target);
     assert!(world.is_player(to));
     assert_eq!(to, target);
-    Some(format!(
-        "You use {}, healing {} hp.",
-        world.get_component::<Name>(item),
-        amount
-    ))
+    Some(
+        LogRecord::new(LogKind::Healing)
+            .target(log_entity(world, to))
+            .item(log_item(world, knowledge, item))
+            .amount(amount),
+    )
 });
 
-handle_event!(confused, |state, cae, world, event| {
+handle_event!(confused, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_cause!(cae, event @ UseOnTarget => item, target);
     extract_label!(event @ Confused => entity);
     assert!(world.is_player(actor));
     assert_eq!(target, entity);
 
-    Some(format!(
-        "You use {} on {}, confusing them for {} turns.",
-        world.get_component::<Name>(item),
-        world.get_component::<Name>(entity),
-        world.get_component::<Confusion>(entity).turns
-    ))
+    Some(
+        LogRecord::new(LogKind::Confused)
+            .actor(log_entity(world, actor))
+            .target(log_entity(world, entity))
+            .item(log_item(world, knowledge, item))
+            .amount(world.get_component::<Confusion>(entity).turns),
+    )
 });
 
-handle_event!(no_stairs_here, |state, cae, world, event| {
+handle_event!(no_stairs_here, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     assert!(world.is_player(actor));
-    Some("There is no way down from here.".to_string())
+    Some(LogRecord::new(LogKind::NoStairsHere))
 });
 
-handle_event!(moved_to_next_level, |state, cae, world, event| {
+handle_event!(moved_to_next_level, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     assert!(world.is_player(actor));
-    Some("You descend to the next level, and take a moment to heal.".to_string())
+    Some(LogRecord::new(LogKind::MovedToNextLevel))
+});
+
+handle_event!(door_opened, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    if !world.is_player(actor) {
+        return None;
+    }
+    Some(LogRecord::new(LogKind::DoorOpened))
+});
+
+handle_event!(door_closed, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    if !world.is_player(actor) {
+        return None;
+    }
+    Some(LogRecord::new(LogKind::DoorClosed))
+});
+
+handle_event!(no_door_to_close, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    if !world.is_player(actor) {
+        return None;
+    }
+    Some(LogRecord::new(LogKind::NoDoorToClose))
+});
+
+handle_event!(nothing_to_explore, |state, cae, world, knowledge, event| {
+    Some(LogRecord::new(LogKind::NothingToExplore))
+});
+
+handle_event!(no_known_stairs, |state, cae, world, knowledge, event| {
+    Some(LogRecord::new(LogKind::NoKnownStairs))
 });
 
-handle_event!(magic_mapping, |state, cae, world, event| {
+handle_event!(magic_mapping, |state, cae, world, knowledge, event| {
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     assert!(world.is_player(actor));
-    Some("The map is revealed to you!".to_string())
+    Some(LogRecord::new(LogKind::MagicMapping))
 });
 
-handle_event!(spotted, |state, cae, world, event| {
+handle_event!(spotted, |state, cae, world, knowledge, event| {
     extract_label!(event @ Spotted => hidden);
-    Some(format!(
-        "You spotted a {}.",
-        world.get_component::<Name>(hidden)
-    ))
+    Some(LogRecord::new(LogKind::Spotted).target(log_entity(world, hidden)))
 });
 
-handle_event!(entry_triggered, |state, cae, world, event| {
+handle_event!(nothing_found, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    if !world.is_player(actor) {
+        return None;
+    }
+    Some(LogRecord::new(LogKind::NothingFound))
+});
+
+handle_event!(cannot_rest, |state, cae, world, knowledge, event| {
+    Some(LogRecord::new(LogKind::CannotRest))
+});
+
+handle_event!(entry_triggered, |state, cae, world, knowledge, event| {
     // If the trigger causes damage, it'll be handled as part of the damage event.
     // TODO if this becomes a repeating pattern, it may be better to create machinery
     //      to handle each node only once
@@ -351,9 +427,197 @@ handle_event!(entry_triggered, |state, cae, world, event| {
     }
     extract_nearest_ancestor!(cae, event @ Turn => actor);
     extract_label!(event @ EntryTriggered => trigger);
-    Some(format!(
-        "{} triggers {}!",
-        world.get_component::<Name>(actor),
-        world.get_component::<Name>(trigger)
-    ))
+    Some(
+        LogRecord::new(LogKind::EntryTriggered)
+            .actor(log_entity(world, actor))
+            .target(log_entity(world, trigger)),
+    )
 });
+
+handle_event!(item_identified, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    extract_label!(event @ ItemIdentified => item);
+    if !world.is_player(actor) {
+        return None;
+    }
+    // The item is named by how it looked, and the target by what it turned out to be
+    let name = world.get_component::<Name>(item);
+    Some(
+        LogRecord::new(LogKind::ItemIdentified)
+            .actor(log_entity(world, actor))
+            .target(log_entity(world, item))
+            .item(LogItem {
+                appearance: Some(knowledge.appearance(&name)?.clone()),
+                ..log_item(world, knowledge, item)
+            }),
+    )
+});
+
+handle_event!(cannot_remove_cursed, |state, cae, world, knowledge, event| {
+    extract_nearest_ancestor!(cae, event @ Turn => actor);
+    extract_cause!(cae, event @ RemoveIntent => item);
+    if !world.is_player(actor) {
+        return None;
+    }
+    Some(LogRecord::new(LogKind::CannotRemoveCursed).item(log_item(world, knowledge, item)))
+});
+
+handle_event!(curse_removed, |state, cae, world, knowledge, event| {
+    extract_label!(event @ CurseRemoved => item);
+    extract_cause!(cae, event @ UseOnTarget => target);
+    if !world.is_player(target) {
+        return None;
+    }
+    Some(
+        LogRecord::new(LogKind::CurseRemoved)
+            .target(log_entity(world, target))
+            .item(log_item(world, knowledge, item)),
+    )
+});
+
+handle_event!(buy_done, |state, cae, world, knowledge, event| {
+    extract_label!(event @ BuyDone => price);
+    extract_cause!(cae, event @ BuyIntent => item);
+    Some(
+        LogRecord::new(LogKind::Bought)
+            .item(log_item(world, knowledge, item))
+            .amount(price),
+    )
+});
+
+handle_event!(sell_done, |state, cae, world, knowledge, event| {
+    extract_label!(event @ SellDone => price);
+    extract_cause!(cae, event @ SellIntent => item);
+    Some(
+        LogRecord::new(LogKind::Sold)
+            .item(log_item(world, knowledge, item))
+            .amount(price),
+    )
+});
+
+handle_event!(cannot_afford, |state, cae, world, knowledge, event| {
+    extract_cause!(cae, event @ BuyIntent => item);
+    Some(LogRecord::new(LogKind::CannotAfford).item(log_item(world, knowledge, item)))
+});
+
+handle_event!(purchase_blocked, |state, cae, world, knowledge, event| {
+    extract_cause!(cae, event @ BuyIntent => item);
+    Some(LogRecord::new(LogKind::PurchaseBlocked).item(log_item(world, knowledge, item)))
+});
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::game_log::{game_log_system, GameLogSystemState};
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    fn records(test: &mut TestWorld, state: GameLogSystemState) -> Vec<LogRecord> {
+        test.run(|schedule| schedule.add_system(game_log_system(state)));
+        test.resources
+            .get::<GameLog>()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|entry| entry.record.clone())
+            .collect()
+    }
+
+    fn goblin(test: &mut TestWorld) -> Entity {
+        test.world.push((
+            Monster,
+            Name::from("Goblin".to_string()),
+            Position::new(6, 5),
+        ))
+    }
+
+    #[test]
+    fn hits_and_misses() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let goblin = goblin(&mut test);
+        let state = GameLogSystemState::new(&test.resources);
+
+        let turn = test.turn(player);
+        let hit = test.cause(&turn, Label::Hit);
+        test.cause(
+            &hit,
+            Label::Damage {
+                to: goblin,
+                amount: 3,
+                bleeding: true,
+            },
+        );
+        let turn = test.turn(goblin);
+        let hit = test.cause(&turn, Label::Hit);
+        test.cause(
+            &hit,
+            Label::Damage {
+                to: player,
+                amount: 0,
+                bleeding: true,
+            },
+        );
+
+        let records = records(&mut test, state);
+        assert_eq!(records.len(), 2);
+        let (hit, miss) = (&records[0], &records[1]);
+        assert_eq!(hit.kind, LogKind::Hit);
+        assert!(hit.actor.as_ref().unwrap().is_player);
+        assert_eq!(hit.target.as_ref().unwrap().name, "Goblin");
+        assert_eq!(hit.amount, Some(3));
+        assert_eq!(hit.causes, vec!["Turn", "Hit", "Damage"]);
+        assert_eq!(miss.kind, LogKind::Miss);
+        assert_eq!(miss.actor.as_ref().unwrap().name, "Goblin");
+        assert!(miss.target.as_ref().unwrap().is_player);
+        assert_eq!(miss.amount, Some(0));
+    }
+
+    #[test]
+    fn items_are_logged_as_the_player_knew_them() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let name = Name::from("Health Potion".to_string());
+        let potion = test.world.push((Item, name.clone()));
+        {
+            let mut knowledge = test.resources.get_mut::<ItemKnowledge>().unwrap();
+            let mut rng = RandomNumberGenerator::seeded(1);
+            knowledge.assign_appearance(&name, Appearance::Potion, &mut rng);
+        }
+        let state = GameLogSystemState::new(&test.resources);
+
+        let turn = test.turn(player);
+        let pickup = test.cause(&turn, Label::PickupAction { item: potion });
+        test.cause(&pickup, Label::PickupDone);
+        let records = records(&mut test, state);
+        test.resources
+            .get_mut::<ItemKnowledge>()
+            .unwrap()
+            .identify(&name);
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, LogKind::Pickup);
+        let item = records[0].item.as_ref().unwrap();
+        assert_eq!(item.name, "Health Potion");
+        assert_eq!(item.enchantment, 0);
+        let knowledge = test.resources.get::<ItemKnowledge>().unwrap();
+        assert_eq!(item.appearance.as_ref(), knowledge.appearance(&name));
+    }
+
+    #[test]
+    fn only_the_player_hears_about_their_own_failures() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let goblin = goblin(&mut test);
+        let state = GameLogSystemState::new(&test.resources);
+
+        let turn = test.turn(goblin);
+        test.cause(&turn, Label::PickupNothingHere);
+        let turn = test.turn(player);
+        test.cause(&turn, Label::PickupNothingHere);
+
+        let records = records(&mut test, state);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, LogKind::PickupNothingHere);
+        assert_eq!(records[0].actor, None);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::systems::prelude::*;
+
+cae_system_state!(IdentificationSystemState {
+    subscribe(ItemIdentified)
+});
+
+#[system]
+#[read_component(Name)]
+#[read_component(Identifiable)]
+pub fn identification(
+    #[state] state: &IdentificationSystemState,
+    #[resource] item_knowledge: &mut ItemKnowledge,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] cae: &mut CauseAndEffect,
+    world: &SubWorld,
+) {
+    // Newly spawned item types get their appearance for this run
+    <(&Name, &Identifiable)>::query().for_each(world, |(name, identifiable)| {
+        item_knowledge.assign_appearance(name, identifiable.appearance, rng);
+    });
+
+    for identified in cae.get_queue(state.item_identified) {
+        extract_label!(identified @ ItemIdentified => item);
+        item_knowledge.identify(&world.get_component::<Name>(item));
+    }
+}
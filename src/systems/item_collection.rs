@@ -5,12 +5,20 @@ cae_system_state!(ItemCollectionSystemState {
 });
 
 #[system]
+#[read_component(Entity)]
 #[read_component(Name)]
 #[read_component(Position)]
+#[read_component(InBackpack)]
+#[read_component(Quantity)]
+#[read_component(Equipped)]
+#[read_component(Weight)]
+#[read_component(CombatStats)]
+#[read_component(Gold)]
 pub fn item_collection(
     #[state] state: &ItemCollectionSystemState,
     #[resource] cae: &mut CauseAndEffect,
     #[resource] map: &Map,
+    #[resource] deferred_cleanup: &mut DeferredCleanup,
     commands: &mut CommandBuffer,
     world: &SubWorld,
 ) {
@@ -30,10 +38,217 @@ pub fn item_collection(
             }
             Some(&item) => {
                 let action = cae.add_effect(&cause, Label::PickupAction { item });
+                if let Ok((gold,)) = <(&Gold,)>::query().get(world, item) {
+                    // Gold goes straight into the purse, it doesn't take up any room
+                    let purse = <(&Gold,)>::query()
+                        .get(world, actor)
+                        .map_or(0, |(purse,)| purse.amount);
+                    commands.add_component(actor, Gold::new(purse + gold.amount));
+                    commands.remove_component::<Position>(item);
+                    deferred_cleanup.entity(item);
+                    cae.add_effect(&action, Label::PickupDone);
+                    continue;
+                }
+
+                let stack = find_stack(world, actor, item);
+                if !has_room(world, actor, item, stack.is_some()) {
+                    cae.add_effect(&action, Label::PickupBlocked);
+                    continue;
+                }
+
                 commands.remove_component::<Position>(item);
-                commands.add_component(item, InBackpack::new(actor));
+                if let Some((stack, merged)) = stack {
+                    // The picked up entity is still needed for logging, so clean up later
+                    commands.add_component(stack, merged);
+                    deferred_cleanup.entity(item);
+                } else {
+                    commands.add_component(item, InBackpack::new(actor));
+                }
                 cae.add_effect(&action, Label::PickupDone);
             }
         }
     }
 }
+
+/// If `item` is stackable and `owner` already carries a stack of the same kind,
+/// return that stack along with its quantity after merging `item` into it
+pub fn find_stack(world: &SubWorld, owner: Entity, item: Entity) -> Option<(Entity, Quantity)> {
+    let (name, quantity) = <(&Name, &Quantity)>::query().get(world, item).ok()?;
+    <(Entity, &InBackpack, &Name, &Quantity)>::query()
+        .iter(world)
+        .find(|(stack, in_backpack, stack_name, _)| {
+            **stack != item && in_backpack.owner == owner && *stack_name == name
+        })
+        .map(|(stack, _, _, stack_quantity)| {
+            (*stack, Quantity::new(stack_quantity.amount + quantity.amount))
+        })
+}
+
+/// Is there both a free slot (unless `item` goes onto an existing stack) and enough strength left
+/// for `owner` to pick up `item`?
+pub fn has_room(world: &SubWorld, owner: Entity, item: Entity, stacks: bool) -> bool {
+    if !stacks {
+        let used_slots = <(&InBackpack,)>::query()
+            .iter(world)
+            .filter(|(in_backpack,)| in_backpack.owner == owner)
+            .count();
+        if used_slots >= MAX_BACKPACK_SLOTS {
+            return false;
+        }
+    }
+
+    let capacity = match <(&CombatStats,)>::query().get(world, owner) {
+        Ok((stats,)) => stats.carry_capacity(),
+        _ => return true,
+    };
+    let item_weight = match <(&Weight, Option<&Quantity>)>::query().get(world, item) {
+        Ok((weight, maybe_quantity)) => weight.total(maybe_quantity),
+        _ => 0.0,
+    };
+    world.carried_weight(owner) + item_weight <= Encumbrance::max_load(capacity)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::item_collection::{
+        find_stack, has_room, item_collection_system, ItemCollectionSystemState,
+    };
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    fn potions(test: &mut TestWorld, amount: i32) -> Entity {
+        test.world.push((
+            Item,
+            Name::from("Health Potion".to_string()),
+            Quantity::new(amount),
+        ))
+    }
+
+    #[test]
+    fn stacks_are_found_by_name() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let vendor = test.world.push((Name::from("Shopkeeper".to_string()),));
+        let stack = potions(&mut test, 2);
+        test.world
+            .entry(stack)
+            .unwrap()
+            .add_component(InBackpack::new(player));
+        let vendor_stack = potions(&mut test, 5);
+        test.world
+            .entry(vendor_stack)
+            .unwrap()
+            .add_component(InBackpack::new(vendor));
+        let found = potions(&mut test, 3);
+        let rations = test
+            .world
+            .push((Item, Name::from("Rations".to_string()), Quantity::new(1)));
+        // Same name, but not stackable
+        let unstackable = test
+            .world
+            .push((Item, Name::from("Health Potion".to_string())));
+
+        test.sub_world(|world| {
+            assert_eq!(
+                find_stack(world, player, found),
+                Some((stack, Quantity::new(5)))
+            );
+            assert_eq!(
+                find_stack(world, vendor, found),
+                Some((vendor_stack, Quantity::new(8)))
+            );
+            assert_eq!(find_stack(world, player, rations), None);
+            assert_eq!(find_stack(world, player, unstackable), None);
+            // A stack doesn't merge into itself
+            assert_eq!(find_stack(world, player, stack), None);
+        });
+    }
+
+    #[test]
+    fn picked_up_items_join_their_stack() {
+        let mut test = TestWorld::default();
+        let position = Position::new(5, 5);
+        let player = test.player(position);
+        let stack = potions(&mut test, 2);
+        test.world
+            .entry(stack)
+            .unwrap()
+            .add_component(InBackpack::new(player));
+        let found = potions(&mut test, 1);
+        test.world.entry(found).unwrap().add_component(position);
+
+        let state = ItemCollectionSystemState::new(&test.resources);
+        let turn = test.turn(player);
+        test.cause(&turn, Label::PickupIntent);
+        test.run(|schedule| schedule.add_system(item_collection_system(state)));
+
+        assert_eq!(test.get_component::<Quantity>(stack), Quantity::new(3));
+        assert!(!test.world.contains(found));
+    }
+
+    fn carry(test: &mut TestWorld, owner: Entity, weight: f32, amount: i32) -> Entity {
+        test.world.push((
+            Item,
+            Name::from("Rock".to_string()),
+            Weight::new(weight),
+            Quantity::new(amount),
+            InBackpack::new(owner),
+        ))
+    }
+
+    #[test]
+    fn carried_weight_counts_backpack_and_equipment() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let vendor = test.world.push((Name::from("Shopkeeper".to_string()),));
+        carry(&mut test, player, 1.5, 2);
+        carry(&mut test, vendor, 100.0, 1);
+        test.world.push((
+            Item,
+            Weight::new(4.0),
+            Equipped {
+                owner: player,
+                slot: EquipmentSlot::Shield,
+            },
+        ));
+        test.world
+            .push((Item, Weight::new(8.0), Position::new(5, 5)));
+
+        assert_eq!(test.sub_world(|world| world.carried_weight(player)), 7.0);
+    }
+
+    #[test]
+    fn room_up_to_max_load() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        // Power 5 makes for a carry capacity of 30, and a max load of 60
+        carry(&mut test, player, 10.0, 5);
+        let exactly = test.world.push((Item, Weight::new(5.0), Quantity::new(2)));
+        let too_much = test.world.push((Item, Weight::new(10.5)));
+        let weightless = test.world.push((Item,));
+
+        test.sub_world(|world| {
+            assert!(has_room(world, player, exactly, false));
+            assert!(!has_room(world, player, too_much, false));
+            assert!(has_room(world, player, weightless, false));
+        });
+    }
+
+    #[test]
+    fn room_in_the_backpack() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        for _ in 0..MAX_BACKPACK_SLOTS - 1 {
+            carry(&mut test, player, 0.0, 1);
+        }
+        let item = test.world.push((Item,));
+        assert!(test.sub_world(|world| has_room(world, player, item, false)));
+
+        carry(&mut test, player, 0.0, 1);
+        test.sub_world(|world| {
+            assert!(!has_room(world, player, item, false));
+            // Going onto an existing stack doesn't take a slot
+            assert!(has_room(world, player, item, true));
+        });
+    }
+}
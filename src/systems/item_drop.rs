@@ -1,4 +1,5 @@
 use crate::systems::prelude::*;
+use crate::util::duplicate::duplicate_entity;
 use legion::EntityStore;
 
 cae_system_state!(ItemDropSystemState {
@@ -9,6 +10,7 @@ cae_system_state!(ItemDropSystemState {
 #[read_component(Name)]
 #[read_component(InBackpack)]
 #[read_component(Position)]
+#[read_component(Quantity)]
 pub fn item_drop(
     #[state] state: &ItemDropSystemState,
     #[resource] cae: &mut CauseAndEffect,
@@ -25,8 +27,94 @@ pub fn item_drop(
             Ok(actor),
             to_drop.get_component::<InBackpack>().map(|b| b.owner)
         );
-        commands.add_component(item, position);
-        commands.remove_component::<InBackpack>(item);
+        match to_drop.get_component::<Quantity>() {
+            // Drop one item off the top of the stack
+            Ok(&Quantity { amount }) if amount > 1 => {
+                commands.add_component(item, Quantity::new(amount - 1));
+                commands.exec_mut(move |w| {
+                    let dropped = duplicate_entity(w, item);
+                    let mut entry = w.entry(dropped).unwrap();
+                    entry.add_component(position);
+                    entry.add_component(Quantity::new(1));
+                    entry.remove_component::<InBackpack>();
+                });
+            }
+            _ => {
+                commands.add_component(item, position);
+                commands.remove_component::<InBackpack>(item);
+            }
+        }
         cae.add_effect(&intent, Label::DropDone);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::item_drop::{item_drop_system, ItemDropSystemState};
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    fn drop(test: &mut TestWorld, actor: Entity, item: Entity) {
+        let state = ItemDropSystemState::new(&test.resources);
+        let turn = test.turn(actor);
+        test.cause(&turn, Label::DropIntent { item });
+        test.run(|schedule| schedule.add_system(item_drop_system(state)));
+    }
+
+    fn on_the_floor(test: &TestWorld) -> Vec<(Entity, Position, Quantity)> {
+        <(Entity, &Position, &Quantity)>::query()
+            .filter(component::<Item>())
+            .iter(&test.world)
+            .map(|(entity, position, quantity)| (*entity, *position, *quantity))
+            .collect()
+    }
+
+    #[test]
+    fn one_item_is_dropped_off_a_stack() {
+        let mut test = TestWorld::default();
+        let position = Position::new(5, 5);
+        let player = test.player(position);
+        let stack = test.world.push((
+            Item,
+            Name::from("Health Potion".to_string()),
+            Quantity::new(3),
+            InBackpack::new(player),
+        ));
+
+        drop(&mut test, player, stack);
+
+        assert_eq!(test.get_component::<Quantity>(stack), Quantity::new(2));
+        assert!(test.has_component::<InBackpack>(stack));
+        let floor = on_the_floor(&test);
+        assert_eq!(floor.len(), 1);
+        let (dropped, dropped_at, quantity) = floor[0];
+        assert_ne!(dropped, stack);
+        assert_eq!((dropped_at, quantity), (position, Quantity::new(1)));
+        assert!(!test.has_component::<InBackpack>(dropped));
+        assert_eq!(
+            test.get_component::<Name>(dropped),
+            test.get_component::<Name>(stack)
+        );
+    }
+
+    #[test]
+    fn the_last_item_is_dropped_whole() {
+        let mut test = TestWorld::default();
+        let position = Position::new(5, 5);
+        let player = test.player(position);
+        let last = test.world.push((
+            Item,
+            Name::from("Health Potion".to_string()),
+            Quantity::new(1),
+            InBackpack::new(player),
+        ));
+
+        drop(&mut test, player, last);
+
+        assert_eq!(
+            on_the_floor(&test),
+            vec![(last, position, Quantity::new(1))]
+        );
+        assert!(!test.has_component::<InBackpack>(last));
+    }
+}
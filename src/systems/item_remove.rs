@@ -6,13 +6,19 @@ cae_system_state!(ItemRemoveSystemState {
 
 #[system]
 #[read_component(Name)]
+#[read_component(Cursed)]
 pub fn item_remove(
     #[state] state: &ItemRemoveSystemState,
     #[resource] cae: &mut CauseAndEffect,
+    world: &SubWorld,
     commands: &mut CommandBuffer,
 ) {
     for remove_intent in cae.get_queue(state.remove_intent) {
         extract_label!(remove_intent @ RemoveIntent => item);
+        if world.has_component::<Cursed>(item) {
+            cae.add_effect(&remove_intent, Label::CannotRemoveCursed);
+            continue;
+        }
         extract_nearest_ancestor!(cae, remove_intent @ Turn => actor);
         commands.remove_component::<Equipped>(item);
         commands.add_component(item, InBackpack { owner: actor });
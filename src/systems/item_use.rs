@@ -18,9 +18,19 @@ cae_system_state!(ItemUseSystemState {
 #[read_component(Equippable)]
 #[read_component(Equipped)]
 #[read_component(HungerClock)]
+#[read_component(Identifiable)]
+#[read_component(Identifies)]
+#[read_component(Cursed)]
+#[read_component(RemovesCurse)]
+#[read_component(Quantity)]
+#[read_component(InBackpack)]
+#[read_component(Name)]
+#[read_component(Entity)]
+#[allow(clippy::too_many_arguments)]
 pub fn item_use(
     #[state] state: &ItemUseSystemState,
     #[resource] map: &Map,
+    #[resource] item_knowledge: &ItemKnowledge,
     #[resource] cae: &mut CauseAndEffect,
     #[resource] deferred_cleanup: &mut DeferredCleanup,
     #[resource] run_state_queue: &mut RunStateQueue,
@@ -77,23 +87,44 @@ pub fn item_use(
             }
         });
 
+        let mut last_successful_use = None;
         for &target in targets {
             let use_on_target = cae.add_effect(&use_intent, Label::UseOnTarget { item, target });
+            let mut used_on_target = false;
             for f in &[
                 provide_healing,
                 inflict_damage,
                 confuse,
                 equip,
                 provide_food,
+                remove_curse,
             ] {
-                used_item |= f(cae, world, commands, &use_on_target);
+                used_on_target |= f(cae, world, commands, &use_on_target);
             }
-            used_item |= magic_mapping(cae, world, &use_on_target, run_state_queue);
+            used_on_target |= magic_mapping(cae, world, &use_on_target, run_state_queue);
+            used_on_target |= identify(cae, world, &use_on_target, item_knowledge);
+            if used_on_target {
+                last_successful_use = Some(use_on_target);
+            }
+            used_item |= used_on_target;
         }
 
         if used_item {
+            // Using an item is the surest way to learn what it is
+            let name = world.get_component::<Name>(item);
+            if world.has_component::<Identifiable>(item) && !item_knowledge.is_identified(&name) {
+                cae.add_effect(
+                    &last_successful_use.unwrap(),
+                    Label::ItemIdentified { item },
+                );
+            }
             if world.has_component::<Consumable>(item) {
-                deferred_cleanup.entity(item);
+                match <(&Quantity,)>::query().get(world, item) {
+                    Ok((&Quantity { amount },)) if amount > 1 => {
+                        commands.add_component(item, Quantity::new(amount - 1))
+                    }
+                    _ => deferred_cleanup.entity(item),
+                }
             }
         } else {
             cae.add_effect(&use_intent, Label::NoValidTargets);
@@ -136,6 +167,7 @@ fn equip(
     let target_slot = equippable.slot;
 
     // Remove any items the target has in the item's slot
+    let mut stuck_with_cursed = false;
     <(Entity, &Equipped)>::query().for_each(world, |(&already_equipped_item, already_equipped)| {
         if already_equipped.owner == target && already_equipped.slot == target_slot {
             cae.add_effect(
@@ -144,9 +176,15 @@ fn equip(
                     item: already_equipped_item,
                 },
             );
+            stuck_with_cursed |= world.has_component::<Cursed>(already_equipped_item);
         }
     });
 
+    // The item_remove system takes care of explaining why the slot can't be freed up
+    if stuck_with_cursed {
+        return true;
+    }
+
     // Wield the item
     commands.add_component(
         item,
@@ -160,6 +198,31 @@ fn equip(
     true
 }
 
+fn remove_curse(
+    cae: &mut CauseAndEffect,
+    world: &SubWorld,
+    commands: &mut CommandBuffer,
+    use_on_target: &Link,
+) -> bool {
+    extract_label!(use_on_target @ UseOnTarget => item, target);
+    if !world.has_component::<RemovesCurse>(item) {
+        return false;
+    }
+
+    <(Entity, Option<&InBackpack>, Option<&Equipped>)>::query()
+        .filter(component::<Cursed>())
+        .for_each(world, |(&cursed_item, maybe_in_backpack, maybe_equipped)| {
+            let owner = maybe_in_backpack
+                .map(|in_backpack| in_backpack.owner)
+                .or_else(|| maybe_equipped.map(|equipped| equipped.owner));
+            if owner == Some(target) {
+                commands.remove_component::<Cursed>(cursed_item);
+                cae.add_effect(&use_on_target, Label::CurseRemoved { item: cursed_item });
+            }
+        });
+    true
+}
+
 fn confuse(
     cae: &mut CauseAndEffect,
     world: &SubWorld,
@@ -291,3 +354,184 @@ fn magic_mapping(
     run_state_queue.push_front(RunState::MagicMapReveal { row: 0 });
     true
 }
+
+fn identify(
+    cae: &mut CauseAndEffect,
+    world: &SubWorld,
+    use_on_target: &Link,
+    item_knowledge: &ItemKnowledge,
+) -> bool {
+    extract_label!(use_on_target @ UseOnTarget => item, target);
+    if !world.has_component::<Identifies>(item) {
+        return false;
+    }
+
+    // Identify each unknown item type in the target's backpack exactly once.
+    // The item being used is skipped, it's identified by virtue of being used.
+    let mut seen = vec![world.get_component::<Name>(item)];
+    <(Entity, &InBackpack, &Name)>::query()
+        .filter(component::<Identifiable>())
+        .for_each(world, |(&other, in_backpack, name)| {
+            if in_backpack.owner != target
+                || seen.contains(name)
+                || item_knowledge.is_identified(name)
+            {
+                return;
+            }
+            seen.push(name.clone());
+            cae.add_effect(&use_on_target, Label::ItemIdentified { item: other });
+        });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::item_remove::{item_remove_system, ItemRemoveSystemState};
+    use crate::systems::item_use::{item_use_system, ItemUseSystemState};
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    fn equipped_dagger(test: &mut TestWorld, owner: Entity) -> Entity {
+        test.world.push((
+            Item,
+            Name::from("Dagger".to_string()),
+            Equippable::new(EquipmentSlot::Melee),
+            MeleePowerBonus::new(2),
+            Equipped {
+                owner,
+                slot: EquipmentSlot::Melee,
+            },
+        ))
+    }
+
+    fn long_sword(test: &mut TestWorld, owner: Entity) -> Entity {
+        test.world.push((
+            Item,
+            Name::from("Long Sword".to_string()),
+            Equippable::new(EquipmentSlot::Melee),
+            MeleePowerBonus::new(4),
+            InBackpack::new(owner),
+        ))
+    }
+
+    /// Uses `item` on its owner, letting `item_remove` handle whatever needs taking off first
+    fn use_item(test: &mut TestWorld, actor: Entity, item: Entity) -> Vec<Label> {
+        let use_state = ItemUseSystemState::new(&test.resources);
+        let remove_state = ItemRemoveSystemState::new(&test.resources);
+        let turn = test.turn(actor);
+        let intent = test.cause(
+            &turn,
+            Label::UseIntent {
+                item,
+                target: UseTarget::SelfCast,
+            },
+        );
+        test.run(|schedule| {
+            schedule
+                .add_system(item_use_system(use_state))
+                .add_system(item_remove_system(remove_state))
+        });
+        test.effects(&intent)
+    }
+
+    #[test]
+    fn cursed_items_cannot_be_removed() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let dagger = equipped_dagger(&mut test, player);
+        test.world.entry(dagger).unwrap().add_component(Cursed);
+
+        let state = ItemRemoveSystemState::new(&test.resources);
+        let turn = test.turn(player);
+        let intent = test.cause(&turn, Label::RemoveIntent { item: dagger });
+        test.run(|schedule| schedule.add_system(item_remove_system(state)));
+
+        assert_eq!(test.effects(&intent), vec![Label::CannotRemoveCursed]);
+        assert!(test.has_component::<Equipped>(dagger));
+        assert!(!test.has_component::<InBackpack>(dagger));
+    }
+
+    #[test]
+    fn equipping_swaps_out_the_old_item() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let dagger = equipped_dagger(&mut test, player);
+        let sword = long_sword(&mut test, player);
+
+        let effects = use_item(&mut test, player, sword);
+
+        assert!(effects.contains(&Label::RemoveDone));
+        assert!(effects.contains(&Label::EquipDone));
+        assert!(test.has_component::<InBackpack>(dagger));
+        assert!(test.has_component::<Equipped>(sword));
+    }
+
+    #[test]
+    fn cursed_items_cannot_be_swapped_out() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let dagger = equipped_dagger(&mut test, player);
+        test.world.entry(dagger).unwrap().add_component(Cursed);
+        let sword = long_sword(&mut test, player);
+
+        let effects = use_item(&mut test, player, sword);
+
+        assert!(effects.contains(&Label::CannotRemoveCursed));
+        assert!(!effects.contains(&Label::EquipDone));
+        assert!(test.has_component::<Equipped>(dagger));
+        assert!(test.has_component::<InBackpack>(sword));
+        assert!(!test.has_component::<Equipped>(sword));
+    }
+
+    #[test]
+    fn remove_curse_lifts_the_curses_of_the_reader_only() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let dagger = equipped_dagger(&mut test, player);
+        test.world.entry(dagger).unwrap().add_component(Cursed);
+        let sword = long_sword(&mut test, player);
+        test.world.entry(sword).unwrap().add_component(Cursed);
+        let vendor = test.world.push((Name::from("Shopkeeper".to_string()),));
+        let for_sale = long_sword(&mut test, vendor);
+        test.world.entry(for_sale).unwrap().add_component(Cursed);
+        let scroll = test.world.push((
+            Item,
+            Name::from("Scroll of Remove Curse".to_string()),
+            RemovesCurse,
+            Consumable,
+            InBackpack::new(player),
+        ));
+
+        let effects = use_item(&mut test, player, scroll);
+
+        assert!(effects.contains(&Label::CurseRemoved { item: dagger }));
+        assert!(effects.contains(&Label::CurseRemoved { item: sword }));
+        assert!(!test.has_component::<Cursed>(dagger));
+        assert!(!test.has_component::<Cursed>(sword));
+        assert!(test.has_component::<Cursed>(for_sale));
+    }
+
+    #[test]
+    fn using_an_item_takes_one_off_the_stack() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let rations = test.world.push((
+            Item,
+            Name::from("Rations".to_string()),
+            ProvidesFood,
+            Consumable,
+            Quantity::new(2),
+            InBackpack::new(player),
+        ));
+
+        use_item(&mut test, player, rations);
+        assert_eq!(test.get_component::<Quantity>(rations), Quantity::new(1));
+
+        let effects = use_item(&mut test, player, rations);
+        assert!(effects.contains(&Label::Ate {
+            who: player,
+            what: rations
+        }));
+        assert!(!test.world.contains(rations));
+    }
+}
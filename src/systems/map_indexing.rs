@@ -7,18 +7,26 @@ use crate::{components::*, resources::*};
 #[read_component(Position)]
 #[read_component(Player)]
 #[read_component(BlocksTile)]
+#[read_component(Door)]
 pub fn map_indexing(#[resource] map: &mut Map, world: &SubWorld) {
     map.populate_blocked();
     map.clear_content_index();
 
-    <(Entity, &Position, Option<&Player>, Option<&BlocksTile>)>::query().for_each(
-        world,
-        |(entity, position, player, blocks)| {
-            map.add_tile_content(*position, *entity);
+    <(
+        Entity,
+        &Position,
+        Option<&Player>,
+        Option<&BlocksTile>,
+        Option<&Door>,
+    )>::query()
+    .for_each(world, |(entity, position, player, blocks, door)| {
+        map.add_tile_content(*position, *entity);
 
-            if player.is_none() && blocks.is_some() {
-                map.block(*position)
-            }
-        },
-    );
+        if player.is_none() && blocks.is_some() {
+            map.block(*position)
+        }
+        if door.map_or(false, |door| !door.open) {
+            map.close_door(*position)
+        }
+    });
 }
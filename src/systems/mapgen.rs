@@ -1,3 +1,4 @@
+use crate::mapgen::MapgenSettings;
 use crate::systems::prelude::*;
 
 #[system]
@@ -6,20 +7,29 @@ pub fn mapgen(
     #[resource] layout: &Layout,
     #[resource] map: &mut Map,
     #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] rex_assets: &RexAssets,
+    #[resource] settings: &MapgenSettings,
     #[resource] run_state_queue: &mut RunStateQueue,
     world: &SubWorld,
     commands: &mut CommandBuffer,
 ) {
-    let mut builder =
-        crate::mapgen::random_builder(rng, layout.map().width(), layout.map().height(), map.depth);
+    let builder = crate::mapgen::build_valid_map(
+        settings,
+        rng,
+        layout.map().width(),
+        layout.map().height(),
+        map.depth,
+        rex_assets,
+    );
 
-    builder.build_map(rng);
     builder.spawn_entities(commands, rng);
 
     crate::mapgen::spawner::player(world, builder.get_starting_position(), commands);
 
     *map = builder.get_map();
 
+    crate::mapgen::spawner::maybe_vendor(rng, map, builder.get_starting_position(), commands);
+
     if cfg!(feature = "visualize-mapgen") {
         run_state_queue.push_front(RunState::MapGeneration {
             snapshots: Box::new(builder.get_snapshots()),
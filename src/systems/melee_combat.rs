@@ -11,7 +11,11 @@ cae_system_state!(MeleeCombatSystemState {
 #[read_component(Equipped)]
 #[read_component(MeleePowerBonus)]
 #[read_component(DefenseBonus)]
+#[read_component(Enchantment)]
 #[read_component(Position)]
+#[read_component(InBackpack)]
+#[read_component(Weight)]
+#[read_component(Quantity)]
 pub fn melee_combat(
     #[state] state: &MeleeCombatSystemState,
     #[resource] cae: &mut CauseAndEffect,
@@ -39,10 +43,7 @@ pub fn melee_combat(
         extract_nearest_ancestor!(cae, melee_intent @ Turn => actor);
 
         // Details about the attacker
-        let (attacker_stats, maybe_attacker_hunger_clock) =
-            <(&CombatStats, Option<&HungerClock>)>::query()
-                .get(world, actor)
-                .unwrap();
+        let (attacker_stats,) = <(&CombatStats,)>::query().get(world, actor).unwrap();
         if attacker_stats.hp <= 0 {
             cae.add_effect(melee_intent, Label::AttackerIsAlreadyDead);
             continue;
@@ -59,32 +60,8 @@ pub fn melee_combat(
         // We don't currently have to-hit / accuracy, so an attack is always a hit
         let hit = cae.add_effect(&melee_action, Label::Hit);
 
-        // Calculate attack power
-        let hunger_attack_power_bonus = maybe_attacker_hunger_clock
-            .map(|clock| match clock.state {
-                HungerState::WellFed => 1,
-                _ => 0,
-            })
-            .unwrap_or(0);
-
-        let equipment_attack_power_bonus = <(&Equipped, &MeleePowerBonus)>::query()
-            .iter(world)
-            .filter(|(equipped, _)| equipped.owner == actor)
-            .map(|(_, bonus)| bonus.power)
-            .sum::<i32>();
-
-        let power: i32 =
-            equipment_attack_power_bonus + attacker_stats.power + hunger_attack_power_bonus;
-
-        // Calculate defense power
-        let defense: i32 = {
-            <(&Equipped, &DefenseBonus)>::query()
-                .iter(world)
-                .filter(|(equipped, _)| equipped.owner == target)
-                .map(|(_, bonus)| bonus.defense)
-                .sum::<i32>()
-                + target_stats.defense
-        };
+        let power = world.melee_power(actor);
+        let defense = world.defense(target);
 
         // Calculate and deal damage
         let damage = i32::max(0, power - defense);
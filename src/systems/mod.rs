@@ -1,9 +1,12 @@
 pub mod ai;
+pub mod autopilot;
 pub mod damage;
 pub mod death;
+pub mod door;
 pub mod entity_cleanup;
 pub mod game_log;
 pub mod hunger;
+pub mod identification;
 pub mod item_collection;
 pub mod item_drop;
 pub mod item_remove;
@@ -17,6 +20,10 @@ pub mod particle;
 pub mod player_action;
 pub mod prelude;
 pub mod render;
+pub mod search;
+#[cfg(test)]
+pub mod testing;
+pub mod trade;
 pub mod trigger;
 pub mod turn;
 pub mod visibility;
@@ -1,9 +1,19 @@
+use std::collections::HashSet;
+
 use crate::systems::prelude::*;
 
 enum Action {
     Move(Vector),
     SkipTurn,
+    Rest,
+    Search,
     DownStairs,
+    CloseDoor,
+    AutoExplore,
+    Travel {
+        destination: Position,
+    },
+    TravelToStairs,
 
     PickUp,
     ShowRemoveItem,
@@ -24,8 +34,43 @@ enum Action {
     Remove {
         choice: i32,
     },
+    MoveTargetCursor {
+        cursor: Position,
+        mouse: Point,
+    },
     CancelTargeting,
 
+    ShowLog {
+        scroll: usize,
+    },
+    CloseLog,
+
+    ShowCharacter,
+    CloseCharacter,
+
+    MoveLookCursor {
+        cursor: Position,
+    },
+    CloseLook,
+
+    ShowKeymap,
+    KeymapSelect {
+        selection: usize,
+    },
+    StartRebinding,
+    Rebind {
+        binding: KeyBinding,
+    },
+    CloseKeymap,
+
+    OpenVendor {
+        vendor: Entity,
+    },
+    SwitchVendorMode,
+    Trade {
+        choice: i32,
+    },
+
     MainMenuSelect {
         selection: MainMenuSelection,
     },
@@ -44,6 +89,7 @@ enum Action {
 #[read_component(Equipped)]
 #[read_component(Ranged)]
 #[read_component(Monster)]
+#[read_component(Vendor)]
 #[read_component(HungerClock)]
 #[read_component(Entity)]
 #[write_component(CombatStats)]
@@ -57,6 +103,9 @@ pub fn player_action(
     #[resource] shown_inventory: &ShownInventory,
     #[resource] run_state_queue: &mut RunStateQueue,
     #[resource] cae: &mut CauseAndEffect,
+    #[resource] keymap: &mut Keymap,
+    #[resource] autopilot: &mut Autopilot,
+    #[resource] game_log: &GameLog,
     world: &mut SubWorld,
     commands: &mut CommandBuffer,
 ) {
@@ -71,7 +120,12 @@ pub fn player_action(
         let input_link = cae.add_effect(&cause, Label::Input { input: *input });
 
         let old_runstate = run_state.clone();
-        let new_runstate = match resolve_action(&old_runstate, input) {
+        let action = if *autopilot != Autopilot::Off && old_runstate == RunState::AwaitingInput {
+            steer(world, map, input, autopilot, cae, &input_link)
+        } else {
+            resolve_action(world, map, keymap, game_log, &old_runstate, input)
+        };
+        let new_runstate = match action {
             Some(Action::Move(vector)) => {
                 try_move_player(world, cae, &input_link, map, vector);
                 RunState::PlayerTurn
@@ -80,6 +134,37 @@ pub fn player_action(
                 cae.add_effect(&input_link, Label::NextLevelIntent);
                 RunState::PlayerTurn
             }
+            Some(Action::CloseDoor) => {
+                cae.add_effect(&input_link, Label::CloseDoorIntent);
+                RunState::PlayerTurn
+            }
+            Some(Action::Rest) => {
+                engage(world, autopilot, Plan::Rest);
+                RunState::AwaitingInput
+            }
+            Some(Action::Search) => {
+                cae.add_effect(&input_link, Label::SearchIntent);
+                RunState::PlayerTurn
+            }
+            Some(Action::AutoExplore) => {
+                engage(world, autopilot, Plan::Explore);
+                RunState::AwaitingInput
+            }
+            Some(Action::Travel { destination }) => {
+                engage(world, autopilot, Plan::Travel { destination });
+                RunState::AwaitingInput
+            }
+            Some(Action::TravelToStairs) => {
+                match known_stairs(world, map) {
+                    Some(destination) => {
+                        engage(world, autopilot, Plan::Travel { destination });
+                    }
+                    None => {
+                        cae.add_effect(&input_link, Label::NoKnownStairs);
+                    }
+                }
+                RunState::AwaitingInput
+            }
             Some(Action::SkipTurn) => {
                 cae.add_effect(&input_link, Label::SkipBecauseInput);
                 skip_turn(world, commands, map);
@@ -97,7 +182,7 @@ pub fn player_action(
             }
 
             Some(Action::Use { choice }) => {
-                try_use(world, cae, &input_link, shown_inventory, choice)
+                try_use(world, cae, &input_link, shown_inventory, input, choice)
                     .unwrap_or(RunState::ShowInventory)
             }
             Some(Action::UseOnTarget { item, target }) => {
@@ -108,7 +193,56 @@ pub fn player_action(
                 }
             }
 
+            Some(Action::MoveTargetCursor { cursor, mouse }) => match old_runstate {
+                RunState::ShowTargeting { range, item, .. } => RunState::ShowTargeting {
+                    range,
+                    item,
+                    cursor,
+                    mouse,
+                },
+                _ => unreachable!(),
+            },
             Some(Action::CancelTargeting) => RunState::AwaitingInput,
+
+            Some(Action::ShowLog { scroll }) => RunState::ShowLog { scroll },
+            Some(Action::CloseLog) => RunState::AwaitingInput,
+
+            Some(Action::ShowCharacter) => RunState::ShowCharacter,
+            Some(Action::CloseCharacter) => RunState::AwaitingInput,
+
+            Some(Action::MoveLookCursor { cursor }) => RunState::ShowLook { cursor },
+            Some(Action::CloseLook) => RunState::AwaitingInput,
+
+            Some(Action::ShowKeymap) => RunState::ShowKeymap {
+                selection: 0,
+                rebinding: false,
+            },
+            Some(Action::KeymapSelect { selection }) => RunState::ShowKeymap {
+                selection,
+                rebinding: false,
+            },
+            Some(Action::StartRebinding) => match old_runstate {
+                RunState::ShowKeymap { selection, .. } => RunState::ShowKeymap {
+                    selection,
+                    rebinding: true,
+                },
+                _ => unreachable!(),
+            },
+            Some(Action::Rebind { binding }) => match old_runstate {
+                RunState::ShowKeymap { selection, .. } => {
+                    let (context, action, _) = keymap.entries().nth(selection).unwrap();
+                    keymap.rebind(context, action, binding);
+                    RunState::ShowKeymap {
+                        selection,
+                        rebinding: false,
+                    }
+                }
+                _ => unreachable!(),
+            },
+            Some(Action::CloseKeymap) => {
+                keymap.save();
+                RunState::AwaitingInput
+            }
             Some(Action::ShowDropItem) => RunState::ShowDropItem,
             Some(Action::Drop { choice }) => {
                 if try_drop(world, cae, &input_link, shown_inventory, choice).is_some() {
@@ -127,6 +261,27 @@ pub fn player_action(
                 }
             }
 
+            Some(Action::OpenVendor { vendor }) => RunState::ShowVendor {
+                vendor,
+                mode: VendorMode::Buy,
+            },
+            Some(Action::SwitchVendorMode) => match old_runstate {
+                RunState::ShowVendor { vendor, mode } => RunState::ShowVendor {
+                    vendor,
+                    mode: mode.switch(),
+                },
+                _ => unreachable!(),
+            },
+            Some(Action::Trade { choice }) => try_trade(
+                world,
+                cae,
+                &input_link,
+                shown_inventory,
+                &old_runstate,
+                choice,
+            )
+            .unwrap_or_else(|| old_runstate.clone()),
+
             Some(Action::MainMenuSelect { selection }) => {
                 old_runstate.with_main_menu_selection(selection)
             }
@@ -148,33 +303,36 @@ pub fn player_action(
         }
     }
 
-    fn resolve_action(runstate: &RunState, input: &Input) -> Option<Action> {
-        // TODO deduplicate patterns like Down|J|Numpad2
-        // (maybe only when we do a proper keymap)
+    fn resolve_action(
+        world: &SubWorld,
+        map: &Map,
+        keymap: &Keymap,
+        game_log: &GameLog,
+        runstate: &RunState,
+        input: &Input,
+    ) -> Option<Action> {
         match runstate {
-            state @ RunState::MainMenu { .. } => match input.key? {
-                VirtualKeyCode::Down | VirtualKeyCode::J | VirtualKeyCode::Numpad2 => {
-                    Some(Action::MainMenuSelect {
+            state @ RunState::MainMenu { .. } => {
+                match keymap.action(KeymapContext::Menu, input)? {
+                    KeyAction::MenuDown => Some(Action::MainMenuSelect {
                         selection: state.main_menu_down(),
-                    })
-                }
-                VirtualKeyCode::Up | VirtualKeyCode::K | VirtualKeyCode::Numpad8 => {
-                    Some(Action::MainMenuSelect {
+                    }),
+                    KeyAction::MenuUp => Some(Action::MainMenuSelect {
                         selection: state.main_menu_up(),
-                    })
+                    }),
+                    // Need .main_menu_selection() trickery due to
+                    // #![feature(bindings_after_at)] being unstable
+                    KeyAction::MenuSelect => match state.main_menu_selection() {
+                        MainMenuSelection::NewGame => Some(Action::NewGame),
+                        MainMenuSelection::LoadGame => Some(Action::LoadGame),
+                        #[cfg(not(target_arch = "wasm32"))]
+                        MainMenuSelection::Quit => Some(Action::Quit),
+                    },
+                    _ => None,
                 }
-                // Need .main_menu_selection() trickery due to
-                // #![feature(bindings_after_at)] being unstable
-                VirtualKeyCode::Return => match state.main_menu_selection() {
-                    MainMenuSelection::NewGame => Some(Action::NewGame),
-                    MainMenuSelection::LoadGame => Some(Action::LoadGame),
-                    #[cfg(not(target_arch = "wasm32"))]
-                    MainMenuSelection::Quit => Some(Action::Quit),
-                },
-                _ => None,
-            },
+            }
 
-            RunState::GameOver => {
+            RunState::GameOver | RunState::Victory => {
                 if input.key.is_some() {
                     Some(Action::Restart)
                 } else {
@@ -182,95 +340,318 @@ pub fn player_action(
                 }
             }
 
-            // TODO factor out "inventory choice" match arm bodies
-            RunState::ShowInventory => match input.key? {
-                VirtualKeyCode::Escape => Some(Action::CloseInventory),
-                key => Some(Action::Use {
-                    choice: letter_to_option(key),
-                }),
-            },
-            RunState::ShowDropItem => match input.key? {
-                VirtualKeyCode::Escape => Some(Action::CloseInventory),
-                key => Some(Action::Drop {
-                    choice: letter_to_option(key),
-                }),
-            },
-            RunState::ShowRemoveItem => match input.key? {
-                VirtualKeyCode::Escape => Some(Action::CloseInventory),
-                key => Some(Action::Remove {
-                    choice: letter_to_option(key),
-                }),
-            },
-
-            RunState::ShowTargeting { item, .. } => {
-                if input.key == Some(VirtualKeyCode::Escape) {
-                    Some(Action::CancelTargeting)
-                } else if input.left_click {
-                    Some(Action::UseOnTarget {
-                        item: *item,
-                        target: input.mouse_pos.into(),
-                    })
-                } else {
-                    None
+            RunState::ShowInventory
+            | RunState::ShowDropItem
+            | RunState::ShowRemoveItem
+            | RunState::ShowVendor { .. } => {
+                let key = input.key?;
+                match keymap.action(KeymapContext::Inventory, input) {
+                    Some(KeyAction::Cancel) => Some(Action::CloseInventory),
+                    Some(KeyAction::SwitchVendorMode)
+                        if matches!(runstate, RunState::ShowVendor { .. }) =>
+                    {
+                        Some(Action::SwitchVendorMode)
+                    }
+                    _ => {
+                        let choice = letter_to_option(key);
+                        Some(match runstate {
+                            RunState::ShowInventory => Action::Use { choice },
+                            RunState::ShowDropItem => Action::Drop { choice },
+                            RunState::ShowRemoveItem => Action::Remove { choice },
+                            _ => Action::Trade { choice },
+                        })
+                    }
                 }
             }
 
-            RunState::AwaitingInput => match input.key? {
-                // Cardinal directions
-                VirtualKeyCode::Up | VirtualKeyCode::K | VirtualKeyCode::Numpad8 => {
-                    Some(Action::Move(Heading::North.into()))
+            RunState::ShowTargeting {
+                range,
+                item,
+                cursor,
+                mouse,
+            } => {
+                let (&player_position, viewshed) = <(&Position, &Viewshed)>::query()
+                    .filter(component::<Player>())
+                    .iter(world)
+                    .next()?;
+                let fire = |target: Position| {
+                    if viewshed.targetable(player_position, *range, target) {
+                        Some(Action::UseOnTarget {
+                            item: *item,
+                            target,
+                        })
+                    } else {
+                        None
+                    }
+                };
+
+                if input.left_click {
+                    return fire(input.mouse_pos.into());
                 }
-
-                VirtualKeyCode::Right | VirtualKeyCode::L | VirtualKeyCode::Numpad6 => {
-                    Some(Action::Move(Heading::East.into()))
+                if input.mouse_pos != *mouse {
+                    return Some(Action::MoveTargetCursor {
+                        cursor: input.mouse_pos.into(),
+                        mouse: input.mouse_pos,
+                    });
                 }
 
-                VirtualKeyCode::Down | VirtualKeyCode::J | VirtualKeyCode::Numpad2 => {
-                    Some(Action::Move(Heading::South.into()))
-                }
+                // Moving the cursor uses the same keys as moving the player
+                let key_action = keymap
+                    .action(KeymapContext::Targeting, input)
+                    .or_else(|| keymap.action(KeymapContext::Game, input))?;
+                let new_cursor = match key_action {
+                    KeyAction::Cancel => return Some(Action::CancelTargeting),
+                    KeyAction::Confirm => return fire(*cursor),
+                    KeyAction::NextTarget => {
+                        next_target(&targets_in_range(world, *range), *cursor)?
+                    }
+                    action => map.clamp(cursor + action.direction()?),
+                };
+                Some(Action::MoveTargetCursor {
+                    cursor: new_cursor,
+                    mouse: *mouse,
+                })
+            }
 
-                VirtualKeyCode::Left | VirtualKeyCode::H | VirtualKeyCode::Numpad4 => {
-                    Some(Action::Move(Heading::West.into()))
+            RunState::ShowLog { scroll } => {
+                let max_scroll = game_log.entries.len().saturating_sub(1);
+                match keymap.action(KeymapContext::Menu, input)? {
+                    KeyAction::MenuUp => Some(Action::ShowLog {
+                        scroll: (scroll + 1).min(max_scroll),
+                    }),
+                    KeyAction::MenuDown => Some(Action::ShowLog {
+                        scroll: scroll.saturating_sub(1),
+                    }),
+                    KeyAction::Cancel => Some(Action::CloseLog),
+                    _ => None,
                 }
+            }
 
-                // Stairs
-                VirtualKeyCode::Period if input.shift => Some(Action::DownStairs),
+            RunState::ShowLook { cursor } => {
+                // Same keys as targeting, without anything to fire
+                let key_action = keymap
+                    .action(KeymapContext::Targeting, input)
+                    .or_else(|| keymap.action(KeymapContext::Game, input))?;
+                if key_action == KeyAction::Cancel {
+                    return Some(Action::CloseLook);
+                }
+                let new_cursor = map.clamp(cursor + key_action.direction()?);
+                let (viewshed,) = <(&Viewshed,)>::query()
+                    .filter(component::<Player>())
+                    .iter(world)
+                    .next()?;
+                // Only places the player has seen can be looked at
+                if viewshed.revealed_tiles.contains(&new_cursor) {
+                    Some(Action::MoveLookCursor { cursor: new_cursor })
+                } else {
+                    None
+                }
+            }
 
-                // Skip turn
-                VirtualKeyCode::Period | VirtualKeyCode::Numpad5 => Some(Action::SkipTurn),
+            RunState::ShowCharacter => match keymap.action(KeymapContext::Menu, input)? {
+                KeyAction::Cancel => Some(Action::CloseCharacter),
+                _ => None,
+            },
 
-                // Diagonals
-                VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => {
-                    Some(Action::Move(Heading::North + Heading::East))
-                }
-                VirtualKeyCode::Numpad7 | VirtualKeyCode::U => {
-                    Some(Action::Move(Heading::North + Heading::West))
+            RunState::ShowKeymap {
+                selection,
+                rebinding: true,
+            } => {
+                let binding = KeyBinding::pressed(input)?;
+                if binding == KeyBinding::new(VirtualKeyCode::Escape) {
+                    Some(Action::KeymapSelect {
+                        selection: *selection,
+                    })
+                } else if KEYS.contains(&binding.key) {
+                    Some(Action::Rebind { binding })
+                } else {
+                    // Modifiers on their own, or keys the config file can't name
+                    None
                 }
-                VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
-                    Some(Action::Move(Heading::South + Heading::East))
+            }
+
+            RunState::ShowKeymap { selection, .. } => {
+                let count = keymap.entries().count();
+                match keymap.action(KeymapContext::Menu, input)? {
+                    KeyAction::MenuUp => Some(Action::KeymapSelect {
+                        selection: (selection + count - 1) % count,
+                    }),
+                    KeyAction::MenuDown => Some(Action::KeymapSelect {
+                        selection: (selection + 1) % count,
+                    }),
+                    KeyAction::MenuSelect => Some(Action::StartRebinding),
+                    KeyAction::Cancel => Some(Action::CloseKeymap),
+                    _ => None,
                 }
-                VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
-                    Some(Action::Move(Heading::South + Heading::West))
+            }
+
+            RunState::AwaitingInput if input.left_click => {
+                let destination = input.mouse_pos.into();
+                let (viewshed,) = <(&Viewshed,)>::query()
+                    .filter(component::<Player>())
+                    .iter(world)
+                    .next()?;
+                if map.contains(destination) && viewshed.revealed_tiles.contains(&destination) {
+                    Some(Action::Travel { destination })
+                } else {
+                    None
                 }
+            }
 
-                // Inventory things
-                VirtualKeyCode::G => Some(Action::PickUp),
-                VirtualKeyCode::I => Some(Action::ShowInventory),
-                VirtualKeyCode::D => Some(Action::ShowDropItem),
-                VirtualKeyCode::R => Some(Action::ShowRemoveItem),
+            RunState::AwaitingInput => match keymap.action(KeymapContext::Game, input)? {
+                KeyAction::DownStairs => Some(Action::DownStairs),
+                // Doors, opening them is done by walking into them
+                KeyAction::CloseDoor => Some(Action::CloseDoor),
+                KeyAction::SkipTurn => Some(Action::SkipTurn),
+                KeyAction::Rest => Some(Action::Rest),
+                KeyAction::Search => Some(Action::Search),
+                KeyAction::AutoExplore => Some(Action::AutoExplore),
+                KeyAction::TravelToStairs => Some(Action::TravelToStairs),
 
+                // Inventory things
+                KeyAction::PickUp => Some(Action::PickUp),
+                KeyAction::ShowInventory => Some(Action::ShowInventory),
+                KeyAction::ShowDropItem => Some(Action::ShowDropItem),
+                KeyAction::ShowRemoveItem => Some(Action::ShowRemoveItem),
+
+                KeyAction::ShowLog => Some(Action::ShowLog { scroll: 0 }),
+                KeyAction::ShowCharacter => Some(Action::ShowCharacter),
+                KeyAction::Look => <(&Position,)>::query()
+                    .filter(component::<Player>())
+                    .iter(world)
+                    .next()
+                    .map(|(&cursor,)| Action::MoveLookCursor { cursor }),
+                KeyAction::ShowKeymap => Some(Action::ShowKeymap),
                 // Save and exit to main menu
-                VirtualKeyCode::Escape => Some(Action::SaveGame),
+                KeyAction::SaveGame => Some(Action::SaveGame),
 
-                // We don't know any other keys
-                _ => None,
-            },
+                // Moves, and nothing for the actions of other contexts
+                action => action.direction().map(Action::Move),
+            }
+            .map(|action| match action {
+                // Bumping into a vendor opens their shop instead of moving
+                Action::Move(vector) => vendor_at(world, map, vector)
+                    .map_or(action, |vendor| Action::OpenVendor { vendor }),
+                _ => action,
+            }),
             // We don't care about key presses during other runstates
             _ => None,
         }
     }
 }
 
+fn engage(world: &SubWorld, autopilot: &mut Autopilot, plan: Plan) {
+    *autopilot = Autopilot::On {
+        plan,
+        watch: watch(world, &HashSet::new()),
+    };
+}
+
+/// Takes the autopilot's next step, unless the player or something in the game interrupts it
+fn steer(
+    world: &SubWorld,
+    map: &Map,
+    input: &Input,
+    autopilot: &mut Autopilot,
+    cae: &mut CauseAndEffect,
+    cause: &Link,
+) -> Option<Action> {
+    // Any key takes back control, and is used up doing so
+    if input.key.is_some() || input.left_click {
+        *autopilot = Autopilot::Off;
+        return None;
+    }
+
+    let (plan, last) = match autopilot {
+        Autopilot::On { plan, watch } => (*plan, watch),
+        Autopilot::Off => return None,
+    };
+    let now = watch(world, &last.items_seen);
+    if last.interrupted_by(&now) {
+        *autopilot = Autopilot::Off;
+        return None;
+    }
+    *last = now;
+
+    let (&position, viewshed) = <(&Position, &Viewshed)>::query()
+        .filter(component::<Player>())
+        .iter(world)
+        .next()?;
+    let action = match plan {
+        Plan::Explore => {
+            let step = explore_step(map, &viewshed.revealed_tiles, position);
+            if step.is_none() {
+                cae.add_effect(cause, Label::NothingToExplore);
+            }
+            step.map(|step| Action::Move(step - position))
+        }
+        Plan::Travel { destination } => {
+            travel_step(map, position, destination).map(|step| Action::Move(step - position))
+        }
+        Plan::Rest => {
+            let stats = world.player_component::<CombatStats>();
+            if stats.hp >= stats.max_hp {
+                None
+            } else if can_heal(world, map) {
+                Some(Action::SkipTurn)
+            } else {
+                cae.add_effect(cause, Label::CannotRest);
+                None
+            }
+        }
+    };
+    if action.is_none() {
+        *autopilot = Autopilot::Off;
+    }
+    action
+}
+
+/// The nearest way down that the player has seen
+fn known_stairs(world: &SubWorld, map: &Map) -> Option<Position> {
+    let (&position, viewshed) = <(&Position, &Viewshed)>::query()
+        .filter(component::<Player>())
+        .iter(world)
+        .next()?;
+    viewshed
+        .revealed_tiles
+        .iter()
+        .filter(|&&tile| map[tile] == TileType::DownStairs)
+        .min_by(|a, b| {
+            position
+                .distance(**a)
+                .partial_cmp(&position.distance(**b))
+                .unwrap()
+        })
+        .copied()
+}
+
+/// How the player is doing, for the autopilot to notice changes. Items that were seen before stay
+/// in `items_seen` even when they're out of view now.
+fn watch(world: &SubWorld, items_seen: &HashSet<Entity>) -> Watch {
+    let (stats, hunger, viewshed) = <(&CombatStats, &HungerClock, &Viewshed)>::query()
+        .filter(component::<Player>())
+        .iter(world)
+        .next()
+        .unwrap();
+    let in_view = |position: &Position| viewshed.visible_tiles.contains(position);
+
+    Watch {
+        hp: stats.hp,
+        hunger: hunger.state,
+        monster_in_view: <&Position>::query()
+            .filter(component::<Monster>())
+            .iter(world)
+            .any(in_view),
+        items_seen: <(&Position, Entity)>::query()
+            .filter(component::<Item>())
+            .iter(world)
+            .filter(|(position, _)| in_view(position))
+            .map(|(_, entity)| *entity)
+            .chain(items_seen.iter().copied())
+            .collect(),
+    }
+}
+
 fn try_move_player(
     world: &mut SubWorld,
     cae: &mut CauseAndEffect,
@@ -295,6 +676,16 @@ fn try_move_player(
         }
     }
 
+    if map.is_door_closed(new_position) {
+        cae.add_effect(
+            &cause,
+            Label::OpenDoorIntent {
+                position: new_position,
+            },
+        );
+        return;
+    }
+
     cae.add_effect(
         &cause,
         Label::MoveIntent {
@@ -303,6 +694,15 @@ fn try_move_player(
     );
 }
 
+fn vendor_at(world: &SubWorld, map: &Map, vector: Vector) -> Option<Entity> {
+    let position = world.player_component::<Position>();
+    let target_position = map.clamp(position + vector);
+    map.get_tile_contents(target_position)?
+        .iter()
+        .find(|&&entity| world.has_component::<Vendor>(entity))
+        .copied()
+}
+
 fn choice_to_entity(shown_inventory: &ShownInventory, choice: i32) -> Option<Entity> {
     let &item = shown_inventory.get(choice as usize)?;
     Some(item)
@@ -341,13 +741,22 @@ fn try_use(
     cae: &mut CauseAndEffect,
     cause: &Link,
     shown_inventory: &ShownInventory,
+    input: &Input,
     choice: i32,
 ) -> Option<RunState> {
     let item = choice_to_entity_from_player_backpack(world, shown_inventory, choice)?;
     if let Ok(ranged) = world.entry_ref(item).unwrap().get_component::<Ranged>() {
+        let range = ranged.range;
+        // Aim at the nearest monster to start with, if there is one
+        let cursor = targets_in_range(world, range)
+            .first()
+            .copied()
+            .unwrap_or_else(|| world.player_component::<Position>());
         Some(RunState::ShowTargeting {
             item,
-            range: ranged.range,
+            range,
+            cursor,
+            mouse: input.mouse_pos,
         })
     } else {
         cae.add_effect(
@@ -361,6 +770,37 @@ fn try_use(
     }
 }
 
+/// Visible monsters within `range` of the player, nearest first
+fn targets_in_range(world: &SubWorld, range: i32) -> Vec<Position> {
+    let (&player_position, viewshed) = <(&Position, &Viewshed)>::query()
+        .filter(component::<Player>())
+        .iter(world)
+        .next()
+        .unwrap();
+    let mut targets: Vec<Position> = <&Position>::query()
+        .filter(component::<Monster>())
+        .iter(world)
+        .copied()
+        .filter(|&position| viewshed.targetable(player_position, range, position))
+        .collect();
+    targets.sort_by(|a, b| {
+        player_position
+            .distance(*a)
+            .partial_cmp(&player_position.distance(*b))
+            .unwrap()
+    });
+    targets
+}
+
+/// The target after the one under the cursor, or the nearest one if the cursor isn't on any
+fn next_target(targets: &[Position], cursor: Position) -> Option<Position> {
+    let next = targets
+        .iter()
+        .position(|&target| target == cursor)
+        .map_or(0, |index| (index + 1) % targets.len());
+    targets.get(next).copied()
+}
+
 fn try_use_on_target(
     world: &mut SubWorld,
     cae: &mut CauseAndEffect,
@@ -413,9 +853,46 @@ fn try_remove(
     Some(())
 }
 
+fn try_trade(
+    world: &SubWorld,
+    cae: &mut CauseAndEffect,
+    cause: &Link,
+    shown_inventory: &ShownInventory,
+    run_state: &RunState,
+    choice: i32,
+) -> Option<RunState> {
+    let (vendor, mode) = match *run_state {
+        RunState::ShowVendor { vendor, mode } => (vendor, mode),
+        _ => unreachable!(),
+    };
+    match mode {
+        VendorMode::Buy => {
+            let item = choice_to_entity(shown_inventory, choice)?;
+            assert_eq!(world.get_component::<InBackpack>(item).owner, vendor);
+            cae.add_effect(cause, Label::BuyIntent { vendor, item });
+        }
+        VendorMode::Sell => {
+            let item = choice_to_entity_from_player_backpack(world, shown_inventory, choice)?;
+            cae.add_effect(cause, Label::SellIntent { vendor, item });
+        }
+    }
+    Some(RunState::ResolveTrade { vendor, mode })
+}
+
 fn skip_turn(world: &mut SubWorld, commands: &mut CommandBuffer, map: &Map) {
-    let player_entity = *world.player_entity();
-    let player_entry = world.entry_ref(player_entity).unwrap();
+    if can_heal(world, map) {
+        let player_entity = *world.player_entity();
+        commands.exec_mut(move |w| {
+            let mut entry = w.entry_mut(player_entity).unwrap();
+            let stats: &mut CombatStats = entry.get_component_mut().unwrap();
+            stats.hp = i32::min(stats.max_hp, stats.hp + 1);
+        });
+    }
+}
+
+/// Skipping a turn only heals when there are no monsters around, and the player isn't hungry
+fn can_heal(world: &SubWorld, map: &Map) -> bool {
+    let player_entry = world.entry_ref(*world.player_entity()).unwrap();
 
     let monsters_visible: bool = player_entry
         .get_component::<Viewshed>()
@@ -431,13 +908,95 @@ fn skip_turn(world: &mut SubWorld, commands: &mut CommandBuffer, map: &Map) {
         HungerState::Hungry | HungerState::Starving
     );
 
-    let can_heal = !monsters_visible && !hungry;
+    !monsters_visible && !hungry
+}
 
-    if can_heal {
-        commands.exec_mut(move |w| {
-            let mut entry = w.entry_mut(player_entity).unwrap();
-            let stats: &mut CombatStats = entry.get_component_mut().unwrap();
-            stats.hp = i32::min(stats.max_hp, stats.hp + 1);
-        });
+#[cfg(test)]
+mod tests {
+    use crate::systems::player_action::{can_heal, next_target, targets_in_range};
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    fn monster(test: &mut TestWorld, position: Position) {
+        test.world.push((Monster, position));
+    }
+
+    #[test]
+    fn targets_are_visible_monsters_in_range_nearest_first() {
+        let mut test = TestWorld::default();
+        test.player(Position::new(5, 5));
+        monster(&mut test, Position::new(8, 5));
+        monster(&mut test, Position::new(6, 5));
+        monster(&mut test, Position::new(5, 9));
+        // Out of range
+        monster(&mut test, Position::new(5, 12));
+        // Out of sight
+        monster(&mut test, Position::new(15, 15));
+        // Not hostile
+        test.world.push((Item, Position::new(5, 6)));
+
+        assert_eq!(
+            test.sub_world(|world| targets_in_range(world, 5)),
+            vec![
+                Position::new(6, 5),
+                Position::new(8, 5),
+                Position::new(5, 9)
+            ]
+        );
+    }
+
+    #[test]
+    fn next_target_wraps_around() {
+        let targets = [
+            Position::new(6, 5),
+            Position::new(8, 5),
+            Position::new(5, 9),
+        ];
+        assert_eq!(next_target(&targets, Position::new(0, 0)), Some(targets[0]));
+        assert_eq!(next_target(&targets, targets[0]), Some(targets[1]));
+        assert_eq!(next_target(&targets, targets[1]), Some(targets[2]));
+        assert_eq!(next_target(&targets, targets[2]), Some(targets[0]));
+        assert_eq!(next_target(&targets[..1], targets[0]), Some(targets[0]));
+        assert_eq!(next_target(&[], targets[0]), None);
+    }
+
+    fn can_heal_now(test: &mut TestWorld) -> bool {
+        // Fills in the tile contents
+        test.run(|schedule| schedule);
+        let map = test.resources.remove::<Map>().unwrap();
+        let result = test.sub_world(|world| can_heal(world, &map));
+        test.resources.insert(map);
+        result
+    }
+
+    #[test]
+    fn no_healing_with_monsters_in_sight() {
+        let mut test = TestWorld::default();
+        test.player(Position::new(5, 5));
+        monster(&mut test, Position::new(15, 15));
+        assert!(can_heal_now(&mut test));
+
+        monster(&mut test, Position::new(9, 5));
+        assert!(!can_heal_now(&mut test));
+    }
+
+    #[test]
+    fn no_healing_while_hungry() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        for (state, heals) in [
+            (HungerState::WellFed, true),
+            (HungerState::Normal, true),
+            (HungerState::Hungry, false),
+            (HungerState::Starving, false),
+        ]
+        .iter()
+        {
+            test.world
+                .entry(player)
+                .unwrap()
+                .add_component(HungerClock::new(*state, 100));
+            assert_eq!(can_heal_now(&mut test), *heals);
+        }
     }
 }
@@ -6,6 +6,8 @@ use bracket_lib::prelude::*;
 use legion::{component, system, world::SubWorld, Entity, EntityStore, IntoQuery};
 use strum::IntoEnumIterator;
 
+use crate::mapgen::{themes::Theme, MapgenSettings};
+use crate::util::i18n::{tr, tr_args};
 use crate::util::world_ext::WorldExt;
 use crate::{
     components::*,
@@ -25,6 +27,19 @@ use crate::{
 #[read_component(Renderable)]
 #[read_component(Viewshed)]
 #[read_component(HungerClock)]
+#[read_component(Enchantment)]
+#[read_component(Quantity)]
+#[read_component(Weight)]
+#[read_component(Gold)]
+#[read_component(Value)]
+#[read_component(Confusion)]
+#[read_component(MeleePowerBonus)]
+#[read_component(DefenseBonus)]
+#[read_component(Hidden)]
+#[read_component(Description)]
+#[read_component(InflictsDamage)]
+#[read_component(Ranged)]
+#[read_component(ProvidesHealing)]
 #[allow(clippy::too_many_arguments)]
 pub fn render(
     world: &SubWorld,
@@ -35,6 +50,11 @@ pub fn render(
     #[resource] input: &Input,
     #[resource] shown_inventory: &mut ShownInventory,
     #[resource] rex_assets: &RexAssets,
+    #[resource] item_knowledge: &ItemKnowledge,
+    #[resource] keymap: &Keymap,
+    #[resource] clock: &Clock,
+    #[resource] statistics: &Statistics,
+    #[resource] mapgen_settings: &MapgenSettings,
 ) {
     let draw_batch = &mut DrawBatch::new();
     draw_batch.cls();
@@ -42,14 +62,51 @@ pub fn render(
     match *run_state {
         RunState::MainMenu { .. } => render_main_menu(run_state, draw_batch, rex_assets),
         RunState::GameOver => render_game_over(draw_batch),
+        RunState::Victory => render_victory(draw_batch),
+        RunState::ShowLog { scroll } => {
+            render_log_history(game_log, layout, input, scroll, keymap, draw_batch)
+        }
+        RunState::ShowCharacter => render_character_sheet(
+            world,
+            layout,
+            item_knowledge,
+            keymap,
+            clock,
+            statistics,
+            draw_batch,
+        ),
         _ => {
-            render_map(world, map, draw_batch, is_mapgen_visualization);
+            render_map(
+                world,
+                map,
+                mapgen_settings.final_depth,
+                draw_batch,
+                is_mapgen_visualization,
+            );
             if !is_mapgen_visualization {
                 render_entities(world, draw_batch);
                 render_gui(world, map, layout, game_log, input, draw_batch);
-                targeting_overlay(world, run_state, map, input, draw_batch);
-                draw_tooltips(world, map, layout, input, draw_batch);
-                show_inventory(world, run_state, layout, shown_inventory, draw_batch);
+                targeting_overlay(world, run_state, map, keymap, draw_batch);
+                look_overlay(
+                    world,
+                    run_state,
+                    map,
+                    layout,
+                    item_knowledge,
+                    keymap,
+                    draw_batch,
+                );
+                draw_tooltips(world, map, layout, input, item_knowledge, draw_batch);
+                show_inventory(
+                    world,
+                    run_state,
+                    layout,
+                    shown_inventory,
+                    item_knowledge,
+                    keymap,
+                    draw_batch,
+                );
+                show_keymap(run_state, layout, keymap, draw_batch);
             }
         }
     };
@@ -90,6 +147,7 @@ fn render_entities(world: &SubWorld, draw_batch: &mut DrawBatch) {
 fn render_map(
     world: &SubWorld,
     map: &Map,
+    final_depth: i32,
     draw_batch: &mut DrawBatch,
     is_mapgen_visualization: bool,
 ) {
@@ -105,12 +163,13 @@ fn render_map(
         player_revealed_tiles(world)
     };
 
+    let palette = Theme::for_depth(map.depth, final_depth).palette();
     for position in &revealed {
         let tile = map[position];
-        let (fg_candidate, glyph) = match tile {
-            TileType::Floor => (RGB::named(GRAY50), to_cp437('.')),
-            TileType::Wall => (RGB::named(GREEN), map.wall_glyph(*position, &revealed)),
-            TileType::DownStairs => (RGB::named(CYAN), to_cp437('>')),
+        let fg_candidate = palette.color(tile);
+        let glyph = match tile {
+            TileType::Wall => map.wall_glyph(*position, &revealed),
+            _ => tile.glyph(),
         };
         let (fg, bg) = if visible.contains(&position) {
             (
@@ -155,7 +214,7 @@ fn render_gui(
     // Show depth
     draw_batch.print_color(
         Point::new(panel_rect.x1 + 2, panel_rect.y1),
-        format!("Depth: {}", map.depth),
+        tr_args("hud.depth", &[("depth", map.depth.to_string())]),
         ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
     );
 
@@ -169,8 +228,19 @@ fn render_gui(
         .iter(world)
         .next()
         .unwrap();
-    let mut health = format!(" HP: {} / {} ", stats.hp, stats.max_hp);
-    health.truncate(max_hp_str_len.try_into().unwrap());
+    let health: String = format!(
+        " {} ",
+        tr_args(
+            "hud.hp",
+            &[
+                ("hp", stats.hp.to_string()),
+                ("max", stats.max_hp.to_string())
+            ],
+        )
+    )
+    .chars()
+    .take(max_hp_str_len.try_into().unwrap())
+    .collect();
 
     draw_batch
         .print_color(
@@ -186,6 +256,19 @@ fn render_gui(
             ColorPair::new(RGB::named(RED), RGB::named(BLACK)),
         );
 
+    // Show gold on the bottom border
+    if let Some((gold,)) = <(&Gold,)>::query()
+        .filter(component::<Player>())
+        .iter(world)
+        .next()
+    {
+        draw_batch.print_color(
+            Point::new(panel_rect.x1 + 2, panel_rect.y2 - bracket_96_workaround),
+            tr_args("status.gold", &[("amount", gold.amount.to_string())]),
+            ColorPair::new(RGB::named(GOLD), RGB::named(BLACK)),
+        );
+    }
+
     // Render game log
     game_log
         .entries
@@ -193,13 +276,14 @@ fn render_gui(
         .rev()
         .take(usize::try_from(panel_rect.height()).unwrap() - 2)
         .enumerate()
-        .for_each(|(i, message)| {
-            draw_batch.print(
+        .for_each(|(i, entry)| {
+            print_log_entry(
+                draw_batch,
                 Point::new(
                     panel_rect.x1 + 2,
                     panel_rect.y1 + 1 + i32::try_from(i).unwrap(),
                 ),
-                message,
+                entry,
             );
         });
 
@@ -210,19 +294,38 @@ fn render_gui(
         .next()
     {
         if let Some((fg, text)) = match clock.state {
-            HungerState::WellFed => Some((GREEN, "Well Fed")),
+            HungerState::WellFed => Some((GREEN, tr("status.well_fed"))),
             HungerState::Normal => None,
-            HungerState::Hungry => Some((ORANGE, "Hungry")),
-            HungerState::Starving => Some((RED, "Starving")),
+            HungerState::Hungry => Some((ORANGE, tr("status.hungry"))),
+            HungerState::Starving => Some((RED, tr("status.starving"))),
         } {
             draw_batch.print_color(
-                layout.hunger_status(text.len() as i32),
+                layout.hunger_status(text.chars().count() as i32),
                 text,
                 ColorPair::new(RGB::named(fg), RGB::named(BLACK)),
             );
         }
     }
 
+    // Encumbrance status
+    let player_entity = *world.player_entity();
+    if let Some((fg, text)) = match world.encumbrance(player_entity) {
+        Encumbrance::Unburdened => None,
+        Encumbrance::Burdened => Some((ORANGE, tr("status.burdened"))),
+        Encumbrance::Overloaded => Some((RED, tr("status.overloaded"))),
+    } {
+        draw_batch.print_color(
+            layout.encumbrance_status(),
+            format!(
+                "{} ({:.1} / {:.1})",
+                text,
+                world.carried_weight(player_entity),
+                stats.carry_capacity()
+            ),
+            ColorPair::new(RGB::named(fg), RGB::named(BLACK)),
+        );
+    }
+
     // Draw mouse cursor
     draw_batch.set_bg(input.mouse_pos, RGB::named(MAGENTA));
 }
@@ -232,6 +335,7 @@ fn draw_tooltips(
     map: &Map,
     layout: &Layout,
     input: &Input,
+    item_knowledge: &ItemKnowledge,
     draw_batch: &mut DrawBatch,
 ) {
     if !map.contains(input.mouse_pos.into()) {
@@ -254,8 +358,11 @@ fn draw_tooltips(
             world
                 .entry_ref(entity)
                 .ok()
-                .filter(|entry| !entry.archetype().layout().has_component::<Hidden>())
-                .and_then(|entry| entry.get_component().ok().map(Name::to_string))
+                .filter(|entry| {
+                    let layout = entry.archetype().layout();
+                    !layout.has_component::<Hidden>() && layout.has_component::<Name>()
+                })
+                .map(|_| world.display_name(entity, item_knowledge))
         })
         .collect();
 
@@ -308,35 +415,109 @@ fn show_inventory(
     run_state: &RunState,
     layout: &Layout,
     shown_inventory: &mut ShownInventory,
+    item_knowledge: &ItemKnowledge,
+    keymap: &Keymap,
     draw_batch: &mut DrawBatch,
 ) {
     if !run_state.show_inventory() {
         return;
     }
 
+    let player_entity = world.player_entity();
     let title = match *run_state {
-        RunState::ShowDropItem => "Drop Which Item?",
-        RunState::ShowInventory => "Inventory",
-        RunState::ShowRemoveItem => "Remove Which Item?",
+        RunState::ShowDropItem => tr("inventory.drop"),
+        RunState::ShowInventory => {
+            let (stats,) = <(&CombatStats,)>::query()
+                .get(world, *player_entity)
+                .unwrap();
+            tr_args(
+                "inventory.title",
+                &[
+                    (
+                        "weight",
+                        format!("{:.1}", world.carried_weight(*player_entity)),
+                    ),
+                    ("capacity", format!("{:.1}", stats.carry_capacity())),
+                ],
+            )
+        }
+        RunState::ShowRemoveItem => tr("inventory.remove"),
+        RunState::ShowVendor { mode, .. } => {
+            let (gold,) = <(&Gold,)>::query().get(world, *player_entity).unwrap();
+            let key = match mode {
+                VendorMode::Buy => "inventory.buy",
+                VendorMode::Sell => "inventory.sell",
+            };
+            tr_args(key, &[("amount", gold.amount.to_string())])
+        }
         _ => panic!(),
     };
 
-    let player_entity = world.player_entity();
-    let inventory: Vec<(&Name, &Entity)> = if *run_state == RunState::ShowRemoveItem {
+    let inventory: Vec<(String, &Entity)> = if *run_state == RunState::ShowRemoveItem {
         <(&Equipped, &Name, Entity)>::query()
             .iter(world)
             .filter(|(equipped, _, _)| equipped.owner == *player_entity)
-            .map(|(_, name, entity)| (name, entity))
+            .map(|(_, _, entity)| (world.display_name(*entity, item_knowledge), entity))
             .collect()
     } else {
-        <(&InBackpack, &Name, Entity)>::query()
+        // When buying, we're looking at the backpack of the vendor
+        let (owner, vendor_mode) = match *run_state {
+            RunState::ShowVendor {
+                vendor,
+                mode: VendorMode::Buy,
+            } => (vendor, Some(VendorMode::Buy)),
+            RunState::ShowVendor { mode, .. } => (*player_entity, Some(mode)),
+            _ => (*player_entity, None),
+        };
+        <(&InBackpack, &Name, Option<&Quantity>, Entity)>::query()
             .iter(world)
-            .filter(|(in_backpack, _, _)| in_backpack.owner == *player_entity)
-            .map(|(_, name, entity)| (name, entity))
+            .filter(|(in_backpack, _, _, _)| in_backpack.owner == owner)
+            .map(|(_, _, maybe_quantity, entity)| {
+                let name = world.display_name(*entity, item_knowledge);
+                let name = match maybe_quantity {
+                    Some(quantity) if quantity.amount > 1 => tr_args(
+                        "inventory.quantity",
+                        &[("amount", quantity.amount.to_string()), ("item", name)],
+                    ),
+                    _ => name,
+                };
+                let price = match vendor_mode {
+                    Some(VendorMode::Buy) => world.price(*entity),
+                    Some(VendorMode::Sell) => world.sell_price(*entity),
+                    None => return (name, entity),
+                };
+                (
+                    tr_args(
+                        "inventory.price",
+                        &[("item", name), ("amount", price.to_string())],
+                    ),
+                    entity,
+                )
+            })
             .collect()
     };
+    let cancel = keymap.describe(KeymapContext::Inventory, KeyAction::Cancel);
+    let footer = if matches!(run_state, RunState::ShowVendor { .. }) {
+        tr_args(
+            "inventory.vendor_footer",
+            &[
+                (
+                    "switch",
+                    keymap.describe(KeymapContext::Inventory, KeyAction::SwitchVendorMode),
+                ),
+                ("cancel", cancel),
+            ],
+        )
+    } else {
+        tr_args("inventory.footer", &[("cancel", cancel)])
+    };
     let count = inventory.len();
-    let max_len = inventory.iter().map(|x| x.0.len()).max().unwrap_or(0);
+    let max_len = inventory
+        .iter()
+        .map(|x| x.0.chars().count())
+        .chain(vec![title.chars().count(), footer.chars().count()])
+        .max()
+        .unwrap_or(0);
 
     let inventory_rect = layout.inventory(count, max_len);
     draw_batch
@@ -346,12 +527,12 @@ fn show_inventory(
         )
         .print_color(
             *inventory_rect.position(Vector::new(3, 0)),
-            title,
+            &title,
             ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
         )
         .print_color(
             *inventory_rect.position(Vector::new(3, -1)),
-            "ESCAPE to cancel",
+            &footer,
             ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
         );
 
@@ -384,17 +565,516 @@ fn show_inventory(
     *shown_inventory = shown_entities.into();
 }
 
+/// Draws a log entry in the color of its category, with the names in it picked out
+fn print_log_entry(draw_batch: &mut DrawBatch, point: Point, entry: &LogEntry) {
+    let category = entry.category();
+    let mut x = point.x;
+    for span in entry.spans() {
+        let fg = if span.highlight {
+            category.highlight_color()
+        } else {
+            category.color()
+        };
+        draw_batch.print_color(
+            Point::new(x, point.y),
+            &span.text,
+            ColorPair::new(fg, RGB::named(BLACK)),
+        );
+        x += i32::try_from(span.text.chars().count()).unwrap();
+    }
+}
+
+/// Every message that's still kept, with the turn it was logged on, newest at the bottom.
+/// Hovering over a message shows what led to it in place of the footer.
+fn render_log_history(
+    game_log: &GameLog,
+    layout: &Layout,
+    input: &Input,
+    scroll: usize,
+    keymap: &Keymap,
+    draw_batch: &mut DrawBatch,
+) {
+    // -1: bracket_lib issue 96 workaround
+    let log_rect = Rect::with_size(0, 0, layout.width - 1, layout.height - 1);
+    let rows = usize::try_from(log_rect.height() - 1).unwrap();
+    let end = game_log.entries.len().saturating_sub(scroll);
+    let start = end.saturating_sub(rows);
+    let shown = &game_log.entries[start..end];
+
+    let hovered = usize::try_from(input.mouse_pos.y - log_rect.y1 - 1)
+        .ok()
+        .and_then(|row| shown.get(row));
+    let footer = match hovered {
+        Some(entry) if !entry.record.causes.is_empty() => entry.record.causes.join(" > "),
+        _ => tr_args(
+            "log_history.footer",
+            &[
+                (
+                    "up",
+                    keymap.describe(KeymapContext::Menu, KeyAction::MenuUp),
+                ),
+                (
+                    "down",
+                    keymap.describe(KeymapContext::Menu, KeyAction::MenuDown),
+                ),
+                (
+                    "cancel",
+                    keymap.describe(KeymapContext::Menu, KeyAction::Cancel),
+                ),
+            ],
+        ),
+    };
+    draw_batch
+        .draw_box(
+            log_rect,
+            ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+        )
+        .print_color(
+            *log_rect.position(Vector::new(3, 0)),
+            tr("log_history.title"),
+            ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+        )
+        .print_color(
+            *log_rect.position(Vector::new(3, -1)),
+            &footer,
+            ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+        );
+
+    for (i, entry) in shown.iter().enumerate() {
+        let y = log_rect.y1 + 1 + i32::try_from(i).unwrap();
+        draw_batch.print_color(
+            Point::new(log_rect.x1 + 2, y),
+            format!("{:>6}", entry.turn),
+            ColorPair::new(entry.category().color(), RGB::named(BLACK)),
+        );
+        print_log_entry(draw_batch, Point::new(log_rect.x1 + 9, y), entry);
+    }
+}
+
+/// The cursor of look mode, and a panel with what's known about the spot under it
+fn look_overlay(
+    world: &SubWorld,
+    run_state: &RunState,
+    map: &Map,
+    layout: &Layout,
+    item_knowledge: &ItemKnowledge,
+    keymap: &Keymap,
+    draw_batch: &mut DrawBatch,
+) {
+    let cursor = match *run_state {
+        RunState::ShowLook { cursor } => cursor,
+        _ => return,
+    };
+    draw_batch.print_color(
+        Point::new(5, 0),
+        tr_args(
+            "look.hint",
+            &[(
+                "cancel",
+                keymap.describe(KeymapContext::Targeting, KeyAction::Cancel),
+            )],
+        ),
+        ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+    );
+    draw_batch.set_bg(*cursor, RGB::named(MAGENTA));
+
+    let mut lines = vec![tr(tile_key(map[&cursor]))];
+    if player_visible_tiles(world).contains(&cursor) {
+        for &entity in map.get_tile_contents(cursor).into_iter().flatten() {
+            lines.extend(describe_entity(world, item_knowledge, entity));
+        }
+    } else {
+        // Things move around while nobody's looking
+        lines.push(tr("look.remembered"));
+    }
+
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = i32::try_from(width).unwrap() + 3;
+    // Stay out of the way of the cursor
+    let x = if cursor.x > layout.width / 2 {
+        1
+    } else {
+        layout.width - width - 2
+    };
+    let panel = Rect::with_size(x, 2, width, i32::try_from(lines.len()).unwrap() + 1);
+    draw_batch.draw_box(panel, ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)));
+    for (i, line) in lines.iter().enumerate() {
+        draw_batch.print_color(
+            Point::new(panel.x1 + 2, panel.y1 + 1 + i32::try_from(i).unwrap()),
+            line,
+            ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+        );
+    }
+}
+
+fn tile_key(tile: TileType) -> &'static str {
+    match tile {
+        TileType::Wall => "tile.wall",
+        TileType::Floor => "tile.floor",
+        TileType::DownStairs => "tile.down_stairs",
+        TileType::ShallowWater => "tile.shallow_water",
+        TileType::DeepWater => "tile.deep_water",
+        TileType::Lava => "tile.lava",
+        TileType::Bridge => "tile.bridge",
+        TileType::Rubble => "tile.rubble",
+        TileType::Grass => "tile.grass",
+        TileType::Chasm => "tile.chasm",
+    }
+}
+
+/// A rough idea of how hurt something is, without giving away exact numbers
+fn health_key(stats: &CombatStats) -> &'static str {
+    let health = stats.hp as f32 / stats.max_hp as f32;
+    if stats.hp >= stats.max_hp {
+        "health.unhurt"
+    } else if health > 0.75 {
+        "health.scratched"
+    } else if health > 0.5 {
+        "health.wounded"
+    } else if health > 0.25 {
+        "health.badly_wounded"
+    } else {
+        "health.nearly_dead"
+    }
+}
+
+/// What look mode tells about one thing. Items keep what they do to themselves until identified.
+fn describe_entity(
+    world: &SubWorld,
+    item_knowledge: &ItemKnowledge,
+    entity: Entity,
+) -> Vec<String> {
+    let entry = match world.entry_ref(entity) {
+        Ok(entry) => entry,
+        Err(_) => return vec![],
+    };
+    let name = match entry.get_component::<Name>() {
+        Ok(name) if entry.get_component::<Hidden>().is_err() => name,
+        _ => return vec![],
+    };
+
+    let mut lines = vec![String::new(), world.display_name(entity, item_knowledge)];
+    if !item_knowledge.is_identified(name) {
+        lines.push(tr("description.unidentified"));
+        return lines;
+    }
+    if let Ok(description) = entry.get_component::<Description>() {
+        lines.push(tr(description));
+    }
+    if let Ok(stats) = entry.get_component::<CombatStats>() {
+        if entry.get_component::<Player>().is_err() {
+            lines.push(tr(health_key(stats)));
+        }
+    }
+
+    let property = |key: &str, amount: i32| tr_args(key, &[("amount", amount.to_string())]);
+    if let Ok(damage) = entry.get_component::<InflictsDamage>() {
+        lines.push(property("look.damage", damage.damage));
+    }
+    if let Ok(ranged) = entry.get_component::<Ranged>() {
+        lines.push(property("look.range", ranged.range));
+    }
+    if let Ok(aoe) = entry.get_component::<AreaOfEffect>() {
+        lines.push(property("look.radius", aoe.radius));
+    }
+    if let Ok(healing) = entry.get_component::<ProvidesHealing>() {
+        lines.push(property("look.healing", healing.heal_amount));
+    }
+    let enchantment = Enchantment::level_of(entry.get_component::<Enchantment>().ok());
+    if let Ok(bonus) = entry.get_component::<MeleePowerBonus>() {
+        lines.push(property("look.power_bonus", bonus.power + enchantment));
+    }
+    if let Ok(bonus) = entry.get_component::<DefenseBonus>() {
+        lines.push(property("look.defense_bonus", bonus.defense + enchantment));
+    }
+    lines
+}
+
+/// Everything about the player that doesn't fit on the bottom panel
+fn render_character_sheet(
+    world: &SubWorld,
+    layout: &Layout,
+    item_knowledge: &ItemKnowledge,
+    keymap: &Keymap,
+    clock: &Clock,
+    statistics: &Statistics,
+    draw_batch: &mut DrawBatch,
+) {
+    let player = *world.player_entity();
+    let (stats, hunger_clock) = <(&CombatStats, &HungerClock)>::query()
+        .get(world, player)
+        .unwrap();
+    let number = |n: i32| n.to_string();
+
+    let hunger_state = tr(match hunger_clock.state {
+        HungerState::WellFed => "status.well_fed",
+        HungerState::Normal => "status.normal",
+        HungerState::Hungry => "status.hungry",
+        HungerState::Starving => "status.starving",
+    });
+    let mut effects = vec![];
+    if let Ok((confusion,)) = <(&Confusion,)>::query().get(world, player) {
+        effects.push(tr_args(
+            "status.confused",
+            &[("amount", number(confusion.turns))],
+        ));
+    }
+    match world.encumbrance(player) {
+        Encumbrance::Unburdened => (),
+        Encumbrance::Burdened => effects.push(tr("status.burdened")),
+        Encumbrance::Overloaded => effects.push(tr("status.overloaded")),
+    }
+    if effects.is_empty() {
+        effects.push(tr("character.no_effects"));
+    }
+
+    let mut lines = vec![
+        tr_args(
+            "character.power",
+            &[
+                ("base", number(stats.power)),
+                ("effective", number(world.melee_power(player))),
+            ],
+        ),
+        tr_args(
+            "character.defense",
+            &[
+                ("base", number(stats.defense)),
+                ("effective", number(world.defense(player))),
+            ],
+        ),
+        tr_args(
+            "character.hp",
+            &[("hp", number(stats.hp)), ("max", number(stats.max_hp))],
+        ),
+        String::new(),
+        tr_args(
+            "character.hunger",
+            &[
+                ("state", hunger_state),
+                ("amount", number(hunger_clock.duration)),
+            ],
+        ),
+        tr_args("character.effects", &[("effects", effects.join(", "))]),
+        String::new(),
+        tr("character.equipment"),
+    ];
+    for slot in EquipmentSlot::iter() {
+        let item = <(Entity, &Equipped)>::query()
+            .iter(world)
+            .find(|(_, equipped)| equipped.owner == player && equipped.slot == slot)
+            .map_or_else(
+                || tr("character.empty_slot"),
+                |(&item, _)| world.display_name(item, item_knowledge),
+            );
+        let slot = tr(match slot {
+            EquipmentSlot::Melee => "slot.melee",
+            EquipmentSlot::Shield => "slot.shield",
+        });
+        lines.push(tr_args("character.slot", &[("slot", slot), ("item", item)]));
+    }
+    lines.push(String::new());
+    lines.push(tr_args(
+        "character.turns",
+        &[("amount", clock.turn.to_string())],
+    ));
+    lines.push(tr_args(
+        "character.kills",
+        &[("amount", statistics.kills.to_string())],
+    ));
+
+    // -1: bracket_lib issue 96 workaround
+    let sheet_rect = Rect::with_size(0, 0, layout.width - 1, layout.height - 1);
+    let footer = tr_args(
+        "character.footer",
+        &[(
+            "cancel",
+            keymap.describe(KeymapContext::Menu, KeyAction::Cancel),
+        )],
+    );
+    draw_batch
+        .draw_box(
+            sheet_rect,
+            ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+        )
+        .print_color(
+            *sheet_rect.position(Vector::new(3, 0)),
+            tr("character.title"),
+            ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+        )
+        .print_color(
+            *sheet_rect.position(Vector::new(3, -1)),
+            &footer,
+            ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+        );
+    for (i, line) in lines.iter().enumerate() {
+        draw_batch.print_color(
+            Point::new(
+                sheet_rect.x1 + 2,
+                sheet_rect.y1 + 2 + i32::try_from(i).unwrap(),
+            ),
+            line,
+            ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+        );
+    }
+}
+
+/// Lists every binding, and doubles as the screen for changing them
+fn show_keymap(run_state: &RunState, layout: &Layout, keymap: &Keymap, draw_batch: &mut DrawBatch) {
+    let (selection, rebinding) = match *run_state {
+        RunState::ShowKeymap {
+            selection,
+            rebinding,
+        } => (selection, rebinding),
+        _ => return,
+    };
+
+    let title = tr("keymap.title");
+    let rows: Vec<String> = keymap
+        .entries()
+        .map(|(context, action, _)| {
+            format!(
+                "{:<12}{:<22}{}",
+                tr(context_key(context)),
+                tr(action_key(action)),
+                keymap.describe(context, action)
+            )
+        })
+        .collect();
+    let footer = if rebinding {
+        let (_, action, _) = keymap.entries().nth(selection).unwrap();
+        tr_args("keymap.rebinding", &[("action", tr(action_key(action)))])
+    } else {
+        tr_args(
+            "keymap.footer",
+            &[
+                (
+                    "select",
+                    keymap.describe(KeymapContext::Menu, KeyAction::MenuSelect),
+                ),
+                (
+                    "cancel",
+                    keymap.describe(KeymapContext::Menu, KeyAction::Cancel),
+                ),
+            ],
+        )
+    };
+    let max_len = rows
+        .iter()
+        .chain(vec![&title, &footer])
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let keymap_rect = layout.inventory(rows.len(), max_len);
+    draw_batch
+        .draw_box(
+            keymap_rect,
+            ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+        )
+        .print_color(
+            *keymap_rect.position(Vector::new(3, 0)),
+            &title,
+            ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+        )
+        .print_color(
+            *keymap_rect.position(Vector::new(3, -1)),
+            &footer,
+            ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
+        );
+
+    for (j, row) in rows.iter().enumerate() {
+        let fg = if j == selection { MAGENTA } else { WHITE };
+        draw_batch.print_color(
+            Point::new(keymap_rect.x1 + 2, keymap_rect.y1 + 2 + j as i32),
+            row,
+            ColorPair::new(RGB::named(fg), RGB::named(BLACK)),
+        );
+    }
+}
+
+fn context_key(context: KeymapContext) -> &'static str {
+    match context {
+        KeymapContext::Game => "context.game",
+        KeymapContext::Menu => "context.menu",
+        KeymapContext::Inventory => "context.inventory",
+        KeymapContext::Targeting => "context.targeting",
+    }
+}
+
+fn action_key(action: KeyAction) -> &'static str {
+    match action {
+        KeyAction::MoveNorth => "action.move_north",
+        KeyAction::MoveNorthEast => "action.move_north_east",
+        KeyAction::MoveEast => "action.move_east",
+        KeyAction::MoveSouthEast => "action.move_south_east",
+        KeyAction::MoveSouth => "action.move_south",
+        KeyAction::MoveSouthWest => "action.move_south_west",
+        KeyAction::MoveWest => "action.move_west",
+        KeyAction::MoveNorthWest => "action.move_north_west",
+        KeyAction::SkipTurn => "action.skip_turn",
+        KeyAction::Rest => "action.rest",
+        KeyAction::Search => "action.search",
+        KeyAction::AutoExplore => "action.auto_explore",
+        KeyAction::TravelToStairs => "action.travel_to_stairs",
+        KeyAction::DownStairs => "action.down_stairs",
+        KeyAction::CloseDoor => "action.close_door",
+        KeyAction::PickUp => "action.pick_up",
+        KeyAction::ShowInventory => "action.show_inventory",
+        KeyAction::ShowDropItem => "action.show_drop_item",
+        KeyAction::ShowRemoveItem => "action.show_remove_item",
+        KeyAction::ShowLog => "action.show_log",
+        KeyAction::ShowCharacter => "action.show_character",
+        KeyAction::Look => "action.look",
+        KeyAction::ShowKeymap => "action.show_keymap",
+        KeyAction::SaveGame => "action.save_game",
+        KeyAction::MenuUp => "action.menu_up",
+        KeyAction::MenuDown => "action.menu_down",
+        KeyAction::MenuSelect => "action.menu_select",
+        KeyAction::SwitchVendorMode => "action.switch_vendor_mode",
+        KeyAction::NextTarget => "action.next_target",
+        KeyAction::Confirm => "action.confirm",
+        KeyAction::Cancel => "action.cancel",
+    }
+}
+
 fn targeting_overlay(
     world: &SubWorld,
     run_state: &RunState,
     map: &Map,
-    input: &Input,
+    keymap: &Keymap,
     draw_batch: &mut DrawBatch,
 ) {
-    if let RunState::ShowTargeting { range, item } = *run_state {
+    if let RunState::ShowTargeting {
+        range,
+        item,
+        cursor,
+        ..
+    } = *run_state
+    {
         draw_batch.print_color(
             Point::new(5, 0),
-            "Select Target:",
+            &tr_args(
+                "targeting.hint",
+                &[
+                    (
+                        "next",
+                        keymap.describe(KeymapContext::Targeting, KeyAction::NextTarget),
+                    ),
+                    (
+                        "fire",
+                        keymap.describe(KeymapContext::Targeting, KeyAction::Confirm),
+                    ),
+                    (
+                        "cancel",
+                        keymap.describe(KeymapContext::Targeting, KeyAction::Cancel),
+                    ),
+                ],
+            ),
             ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
         );
 
@@ -405,19 +1085,13 @@ fn targeting_overlay(
             .next()
             .unwrap();
 
-        let range_f32 = range as f32;
-        let available_cells = &viewshed
-            .visible_tiles
-            .iter()
-            .map(|&position| *position)
-            .filter(|point| DistanceAlg::Pythagoras.distance2d(*player_pos, *point) <= range_f32)
-            .collect::<Vec<Point>>();
-
-        for tile in available_cells {
-            draw_batch.set_bg(*tile, RGB::named(BLUE));
+        for &tile in &viewshed.visible_tiles {
+            if viewshed.targetable(player_pos, range, tile) {
+                draw_batch.set_bg(*tile, RGB::named(BLUE));
+            }
         }
 
-        let valid_aim = available_cells.contains(&input.mouse_pos);
+        let valid_aim = viewshed.targetable(player_pos, range, cursor);
 
         // Highlight AoE, if applicable
         if valid_aim {
@@ -426,7 +1100,7 @@ fn targeting_overlay(
                 .unwrap()
                 .get_component::<AreaOfEffect>()
             {
-                let affected_cells = field_of_view(input.mouse_pos, aoe.radius, map)
+                let affected_cells = field_of_view(*cursor, aoe.radius, map)
                     .iter()
                     .filter(|&p| viewshed.revealed_tiles.contains(&Position::from(*p)))
                     .cloned()
@@ -437,9 +1111,8 @@ fn targeting_overlay(
             }
         }
 
-        // Draw mouse cursor
         draw_batch.set_bg(
-            input.mouse_pos,
+            *cursor,
             if valid_aim {
                 RGB::named(CYAN)
             } else {
@@ -460,18 +1133,10 @@ fn render_main_menu(run_state: &RunState, draw_batch: &mut DrawBatch, rex_assets
             Rect::with_size(24, 18, 31, 11),
             ColorPair::new(WHEAT, BLACK),
         );
-        draw_batch.print_color_centered(
-            20,
-            "Rust Roguelike Tutorial",
-            ColorPair::new(YELLOW, BLACK),
-        );
-        draw_batch.print_color_centered(21, "by Herbert Wolverson", ColorPair::new(CYAN, BLACK));
-        draw_batch.print_color_centered(22, "mucking about: @abesto", ColorPair::new(CYAN, BLACK));
-        draw_batch.print_color_centered(
-            23,
-            "Use Up/Down Arrows and Enter",
-            ColorPair::new(GRAY, BLACK),
-        );
+        draw_batch.print_color_centered(20, tr("menu.title"), ColorPair::new(YELLOW, BLACK));
+        draw_batch.print_color_centered(21, tr("menu.author"), ColorPair::new(CYAN, BLACK));
+        draw_batch.print_color_centered(22, tr("menu.credits"), ColorPair::new(CYAN, BLACK));
+        draw_batch.print_color_centered(23, tr("menu.hint"), ColorPair::new(GRAY, BLACK));
 
         for (i, item) in MainMenuSelection::iter().enumerate() {
             draw_batch.print_color_centered(
@@ -495,23 +1160,42 @@ fn render_main_menu(run_state: &RunState, draw_batch: &mut DrawBatch, rex_assets
 fn render_game_over(draw_batch: &mut DrawBatch) {
     draw_batch.print_color_centered(
         15,
-        "Your journey has ended!",
+        tr("game_over.title"),
         ColorPair::new(RGB::named(YELLOW), RGB::named(BLACK)),
     );
     draw_batch.print_color_centered(
         17,
-        "One day, we'll tell you all about how you did.",
+        tr("game_over.line1"),
         ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
     );
     draw_batch.print_color_centered(
         18,
-        "That day, sadly, is not in this chapter...",
+        tr("game_over.line2"),
+        ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
+    );
+
+    draw_batch.print_color_centered(
+        20,
+        tr("screen.any_key"),
+        ColorPair::new(RGB::named(MAGENTA), RGB::named(BLACK)),
+    );
+}
+
+fn render_victory(draw_batch: &mut DrawBatch) {
+    draw_batch.print_color_centered(
+        15,
+        tr("victory.title"),
+        ColorPair::new(RGB::named(GOLD), RGB::named(BLACK)),
+    );
+    draw_batch.print_color_centered(
+        17,
+        tr("victory.line1"),
         ColorPair::new(RGB::named(WHITE), RGB::named(BLACK)),
     );
 
     draw_batch.print_color_centered(
         20,
-        "Press any key to return to the menu.",
+        tr("screen.any_key"),
         ColorPair::new(RGB::named(MAGENTA), RGB::named(BLACK)),
     );
 }
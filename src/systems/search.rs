@@ -0,0 +1,97 @@
+use crate::systems::prelude::*;
+
+cae_system_state!(SearchSystemState { subscribe(SearchIntent) });
+
+/// How far from themselves a searcher looks
+const SEARCH_RADIUS: f32 = 2.5;
+/// Searching finds each hidden thing nearby with a chance of one in this. Just looking around only
+/// gets one in 24, see `visibility_system`.
+const SEARCH_ODDS: i32 = 3;
+
+#[system]
+#[read_component(Position)]
+#[read_component(Viewshed)]
+#[read_component(Hidden)]
+pub fn search(
+    #[state] state: &SearchSystemState,
+    #[resource] cae: &mut CauseAndEffect,
+    #[resource] map: &Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    world: &SubWorld,
+    commands: &mut CommandBuffer,
+) {
+    for intent in cae.get_queue(state.search_intent) {
+        extract_nearest_ancestor!(cae, intent @ Turn => actor);
+        let (&position, viewshed) = <(&Position, &Viewshed)>::query().get(world, actor).unwrap();
+
+        let mut found_any = false;
+        for &tile in &viewshed.visible_tiles {
+            if position.distance(tile) > SEARCH_RADIUS {
+                continue;
+            }
+            for &entity in map.get_tile_contents(tile).into_iter().flatten() {
+                if world.has_component::<Hidden>(entity) && rng.roll_dice(1, SEARCH_ODDS) == 1 {
+                    cae.add_effect(&intent, Label::Spotted { hidden: entity });
+                    commands.remove_component::<Hidden>(entity);
+                    found_any = true;
+                }
+            }
+        }
+
+        if !found_any {
+            cae.add_effect(&intent, Label::NothingFound);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::prelude::*;
+    use crate::systems::search::{search_system, SearchSystemState};
+    use crate::systems::testing::TestWorld;
+
+    fn search(test: &mut TestWorld, player: Entity) -> Vec<Label> {
+        let state = SearchSystemState::new(&test.resources);
+        let turn = test.turn(player);
+        let intent = test.cause(&turn, Label::SearchIntent);
+        test.run(|schedule| schedule.add_system(search_system(state)));
+        test.effects(&intent)
+    }
+
+    #[test]
+    fn finds_about_one_in_three_nearby() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        for _ in 0..60 {
+            test.world.push((Hidden, Position::new(6, 6)));
+        }
+
+        let found = search(&mut test, player).len();
+        assert!((10..=30).contains(&found), "found {} of 60", found);
+    }
+
+    #[test]
+    fn nothing_is_found_beyond_the_radius() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let near = test.world.push((Hidden, Position::new(7, 6)));
+        let far = test.world.push((Hidden, Position::new(5, 8)));
+
+        let mut searches = 0;
+        while test.has_component::<Hidden>(near) {
+            assert!(searches < 20, "never found the hidden thing next door");
+            let effects = search(&mut test, player);
+            if test.has_component::<Hidden>(near) {
+                assert_eq!(effects, vec![Label::NothingFound]);
+            } else {
+                assert_eq!(effects, vec![Label::Spotted { hidden: near }]);
+            }
+            searches += 1;
+        }
+
+        for _ in 0..20 {
+            assert_eq!(search(&mut test, player), vec![Label::NothingFound]);
+        }
+        assert!(test.has_component::<Hidden>(far));
+    }
+}
@@ -0,0 +1,121 @@
+//! A small world for running systems against in tests
+
+use legion::systems::Builder;
+use legion::{Schedule, World};
+
+use crate::systems::entity_cleanup::entity_cleanup_system;
+use crate::systems::map_indexing::map_indexing_system;
+use crate::systems::prelude::*;
+
+pub struct TestWorld {
+    pub world: World,
+    pub resources: Resources,
+}
+
+impl Default for TestWorld {
+    fn default() -> Self {
+        let mut resources = Resources::default();
+        resources.insert(CauseAndEffect::default());
+        resources.insert(Map::new(20, 20, 1));
+        resources.insert(RandomNumberGenerator::seeded(1));
+        resources.insert(DeferredCleanup::default());
+        resources.insert(RunStateQueue::default());
+        resources.insert(ItemKnowledge::default());
+        resources.insert(Statistics::default());
+        resources.insert(GameLog::default());
+        resources.insert(Clock::default());
+        TestWorld {
+            world: World::default(),
+            resources,
+        }
+    }
+}
+
+impl TestWorld {
+    /// Sees everything within a few steps, and isn't hungry enough to be well fed or weak
+    pub fn player(&mut self, position: Position) -> Entity {
+        let mut viewshed = Viewshed::new(8);
+        viewshed.visible_tiles = self
+            .resources
+            .get::<Map>()
+            .unwrap()
+            .position_set()
+            .into_iter()
+            .filter(|tile| position.distance(*tile) <= 8.0)
+            .collect();
+        self.world.push((
+            Player,
+            position,
+            viewshed,
+            Name::from("Player".to_string()),
+            CombatStats {
+                max_hp: 30,
+                hp: 30,
+                defense: 2,
+                power: 5,
+            },
+            HungerClock::new(HungerState::Normal, 100),
+            Gold::new(0),
+        ))
+    }
+
+    /// Starts a turn for `actor`, for intents to hang off
+    pub fn turn(&mut self, actor: Entity) -> Link {
+        let mut cae = self.resources.get_mut::<CauseAndEffect>().unwrap();
+        let root = cae.get_root();
+        cae.add_effect(&root, Label::Turn { actor })
+    }
+
+    pub fn cause(&mut self, cause: &Link, label: Label) -> Link {
+        self.resources
+            .get_mut::<CauseAndEffect>()
+            .unwrap()
+            .add_effect(cause, label)
+    }
+
+    /// Everything that happened because of `cause`, however indirectly
+    #[must_use]
+    pub fn effects(&self, cause: &Link) -> Vec<Label> {
+        let cae = self.resources.get::<CauseAndEffect>().unwrap();
+        let mut effects = vec![];
+        let mut queue = cae.get_effects(cause);
+        while let Some(effect) = queue.pop() {
+            effects.push(effect.label);
+            queue.extend(cae.get_effects(&effect));
+        }
+        effects
+    }
+
+    /// Indexes the map, runs `systems` once, then removes what they deleted, like a frame would
+    pub fn run(&mut self, systems: impl FnOnce(&mut Builder) -> &mut Builder) {
+        let mut builder = Schedule::builder();
+        builder.add_system(map_indexing_system()).flush();
+        systems(&mut builder)
+            .flush()
+            .add_system(entity_cleanup_system());
+        builder
+            .build()
+            .execute(&mut self.world, &mut self.resources);
+    }
+
+    pub fn has_component<T: legion::storage::Component>(&self, entity: Entity) -> bool {
+        self.world
+            .entry_ref(entity)
+            .map_or(false, |entry| entry.get_component::<T>().is_ok())
+    }
+
+    #[must_use]
+    pub fn get_component<T: legion::storage::Component + Clone>(&self, entity: Entity) -> T {
+        self.world
+            .entry_ref(entity)
+            .unwrap()
+            .get_component::<T>()
+            .unwrap()
+            .clone()
+    }
+
+    /// For calling the `WorldExt` helpers
+    pub fn sub_world<T>(&mut self, f: impl FnOnce(&SubWorld) -> T) -> T {
+        f(&SubWorld::from(&mut self.world))
+    }
+}
@@ -0,0 +1,183 @@
+use crate::systems::item_collection::{find_stack, has_room};
+use crate::systems::prelude::*;
+use crate::util::duplicate::duplicate_entity;
+
+cae_system_state!(TradeSystemState {
+    subscribe(BuyIntent, SellIntent)
+});
+
+#[system]
+#[read_component(Entity)]
+#[read_component(Name)]
+#[read_component(InBackpack)]
+#[read_component(Equipped)]
+#[read_component(Quantity)]
+#[read_component(Weight)]
+#[read_component(CombatStats)]
+#[read_component(Value)]
+#[read_component(Enchantment)]
+#[read_component(Gold)]
+pub fn trade(
+    #[state] state: &TradeSystemState,
+    #[resource] cae: &mut CauseAndEffect,
+    #[resource] deferred_cleanup: &mut DeferredCleanup,
+    world: &SubWorld,
+    commands: &mut CommandBuffer,
+) {
+    for intent in cae.get_queue(state.buy_intent) {
+        extract_label!(intent @ BuyIntent => vendor, item);
+        extract_nearest_ancestor!(cae, intent @ Turn => actor);
+        assert_eq!(world.get_component::<InBackpack>(item).owner, vendor);
+
+        let price = world.price(item);
+        let gold = world.get_component::<Gold>(actor);
+        if gold.amount < price {
+            cae.add_effect(&intent, Label::CannotAfford);
+            continue;
+        }
+        let stack = find_stack(world, actor, item);
+        if !has_room(world, actor, item, stack.is_some()) {
+            cae.add_effect(&intent, Label::PurchaseBlocked);
+            continue;
+        }
+
+        commands.add_component(actor, Gold::new(gold.amount - price));
+        if let Some((stack, merged)) = stack {
+            // The bought entity is still needed for logging, so clean up later
+            commands.add_component(stack, merged);
+            deferred_cleanup.entity(item);
+        } else {
+            commands.add_component(item, InBackpack::new(actor));
+        }
+        cae.add_effect(&intent, Label::BuyDone { price });
+    }
+
+    for intent in cae.get_queue(state.sell_intent) {
+        extract_label!(intent @ SellIntent => vendor, item);
+        extract_nearest_ancestor!(cae, intent @ Turn => actor);
+        assert_eq!(world.get_component::<InBackpack>(item).owner, actor);
+
+        let price = world.sell_price(item);
+        let gold = world.get_component::<Gold>(actor);
+        commands.add_component(actor, Gold::new(gold.amount + price));
+
+        match <(&Quantity,)>::query().get(world, item) {
+            // Sell one item off the top of the stack
+            Ok((&Quantity { amount },)) if amount > 1 => {
+                commands.add_component(item, Quantity::new(amount - 1));
+                commands.exec_mut(move |w| {
+                    let sold = duplicate_entity(w, item);
+                    let mut entry = w.entry(sold).unwrap();
+                    entry.add_component(Quantity::new(1));
+                    entry.add_component(InBackpack::new(vendor));
+                });
+            }
+            _ => commands.add_component(item, InBackpack::new(vendor)),
+        }
+        cae.add_effect(&intent, Label::SellDone { price });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+    use crate::systems::trade::{trade_system, TradeSystemState};
+
+    fn potions(test: &mut TestWorld, owner: Entity, amount: i32) -> Entity {
+        test.world.push((
+            Item,
+            Name::from("Health Potion".to_string()),
+            Value::new(10),
+            Quantity::new(amount),
+            InBackpack::new(owner),
+        ))
+    }
+
+    fn trade(test: &mut TestWorld, player: Entity, label: Label) -> Vec<Label> {
+        let state = TradeSystemState::new(&test.resources);
+        let turn = test.turn(player);
+        let intent = test.cause(&turn, label);
+        test.run(|schedule| schedule.add_system(trade_system(state)));
+        test.effects(&intent)
+    }
+
+    fn setup(gold: i32) -> (TestWorld, Entity, Entity) {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        test.world
+            .entry(player)
+            .unwrap()
+            .add_component(Gold::new(gold));
+        let vendor = test.world.push((Name::from("Shopkeeper".to_string()),));
+        (test, player, vendor)
+    }
+
+    #[test]
+    fn bought_items_join_their_stack() {
+        let (mut test, player, vendor) = setup(25);
+        let stack = potions(&mut test, player, 2);
+        let item = potions(&mut test, vendor, 1);
+
+        let effects = trade(&mut test, player, Label::BuyIntent { vendor, item });
+
+        assert_eq!(effects, vec![Label::BuyDone { price: 10 }]);
+        assert_eq!(test.get_component::<Gold>(player), Gold::new(15));
+        assert_eq!(test.get_component::<Quantity>(stack), Quantity::new(3));
+        assert!(!test.world.contains(item));
+    }
+
+    #[test]
+    fn one_item_is_sold_off_a_stack() {
+        let (mut test, player, vendor) = setup(0);
+        let stack = potions(&mut test, player, 3);
+
+        let effects = trade(
+            &mut test,
+            player,
+            Label::SellIntent {
+                vendor,
+                item: stack,
+            },
+        );
+
+        assert_eq!(effects, vec![Label::SellDone { price: 5 }]);
+        assert_eq!(test.get_component::<Gold>(player), Gold::new(5));
+        assert_eq!(test.get_component::<Quantity>(stack), Quantity::new(2));
+        let sold = <(&InBackpack, &Quantity)>::query()
+            .iter(&test.world)
+            .filter(|(in_backpack, _)| in_backpack.owner == vendor)
+            .map(|(_, quantity)| *quantity)
+            .collect::<Vec<_>>();
+        assert_eq!(sold, vec![Quantity::new(1)]);
+    }
+
+    #[test]
+    fn cannot_buy_without_enough_gold() {
+        let (mut test, player, vendor) = setup(9);
+        let item = potions(&mut test, vendor, 1);
+
+        let effects = trade(&mut test, player, Label::BuyIntent { vendor, item });
+
+        assert_eq!(effects, vec![Label::CannotAfford]);
+        assert_eq!(test.get_component::<Gold>(player), Gold::new(9));
+        assert_eq!(test.get_component::<InBackpack>(item).owner, vendor);
+    }
+
+    #[test]
+    fn cannot_buy_without_room() {
+        let (mut test, player, vendor) = setup(100);
+        for _ in 0..MAX_BACKPACK_SLOTS {
+            test.world.push((Item, InBackpack::new(player)));
+        }
+        let item = test
+            .world
+            .push((Item, Value::new(10), InBackpack::new(vendor)));
+
+        let effects = trade(&mut test, player, Label::BuyIntent { vendor, item });
+
+        assert_eq!(effects, vec![Label::PurchaseBlocked]);
+        assert_eq!(test.get_component::<Gold>(player), Gold::new(100));
+        assert_eq!(test.get_component::<InBackpack>(item).owner, vendor);
+    }
+}
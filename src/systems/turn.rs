@@ -11,12 +11,14 @@ use crate::{components::*, resources::*};
 pub fn turn(
     #[resource] run_state: &RunState,
     #[resource] cae: &mut CauseAndEffect,
+    #[resource] clock: &mut Clock,
     world: &SubWorld,
 ) {
     // Decide what actors take action this turn
     // This is where the core of an energy system would live
     match *run_state {
         RunState::MonsterTurn => {
+            clock.turn += 1;
             <(Entity,)>::query()
                 .filter(component::<Monster>())
                 .for_each(world, |(&actor,)| {
@@ -25,7 +27,10 @@ pub fn turn(
         }
         // No taking turns on the main menu
         // Also, on PlayerTurn, we already have a Label::Turn left over from RunState::AwaitingInput.
-        RunState::MainMenu { .. } | RunState::GameOver | RunState::PlayerTurn => (),
+        RunState::MainMenu { .. }
+        | RunState::GameOver
+        | RunState::Victory
+        | RunState::PlayerTurn => (),
         _ => {
             cae.add_effect(
                 &cae.get_root(),
@@ -0,0 +1,83 @@
+use legion::{world::Duplicate, Entity, World};
+
+use crate::components::*;
+
+// Components must be listed explicitly, in `foreach_component`, like for saving.
+macro_rules! register_clones {
+    ($merger:ident, $($component:ident),+) => {
+        $(
+            $merger.register_clone::<$component>();
+        )+
+    }
+}
+
+fn merger() -> Duplicate {
+    let mut merger = Duplicate::default();
+    foreach_component!(register_clones!(merger,));
+    merger
+}
+
+/// Create a copy of `entity` in the same world, returning the new entity
+pub fn duplicate_entity(world: &mut World, entity: Entity) -> Entity {
+    let mut merger = merger();
+    // `clone_from_single` can't read from and write to the same world, so go through a scratch world
+    let mut scratch = World::default();
+    let in_scratch = scratch.clone_from_single(world, entity, &mut merger);
+    world.clone_from_single(&scratch, in_scratch, &mut merger)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use legion::{systems::CommandBuffer, Entity, Resources, World};
+
+    use crate::components::{Cursed, Enchantment, Position};
+    use crate::mapgen::spawner;
+    use crate::util::duplicate::duplicate_entity;
+
+    #[test]
+    fn duplicates_keep_every_component() {
+        let mut world = World::default();
+        let mut commands = CommandBuffer::new(&world);
+        let spawners: &[fn(&mut CommandBuffer) -> Entity] = &[
+            spawner::orc,
+            spawner::vendor,
+            spawner::health_potion,
+            spawner::fireball_scroll,
+            spawner::identify_scroll,
+            spawner::remove_curse_scroll,
+            spawner::magic_mapping_scroll,
+            spawner::tower_shield,
+            spawner::ration,
+            spawner::bear_trap,
+            spawner::door,
+        ];
+        let mut originals: Vec<Entity> =
+            spawners.iter().map(|spawn| spawn(&mut commands)).collect();
+        originals.push(spawner::gold_pile(&mut commands, 10));
+        // Components added after spawning, like the ones `enchant` hands out
+        let dagger = spawner::dagger(&mut commands);
+        commands.add_component(dagger, Position::new(1, 2));
+        commands.add_component(dagger, Enchantment::new(-1));
+        commands.add_component(dagger, Cursed);
+        originals.push(dagger);
+        commands.flush(&mut world, &mut Resources::default());
+
+        for original in originals {
+            let copy = duplicate_entity(&mut world, original);
+            let components = |entity| -> HashSet<_> {
+                world
+                    .entry_ref(entity)
+                    .unwrap()
+                    .archetype()
+                    .layout()
+                    .component_types()
+                    .iter()
+                    .copied()
+                    .collect()
+            };
+            assert_eq!(components(copy), components(original));
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Player-facing text, looked up by key in the catalog of the chosen language.
+//!
+//! Catalogs live in `assets/locale`, one `key = template` per line. Templates refer to their
+//! arguments as `{name}`, and pick a word form by a numeric argument with `{name|one|other}`.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, EnumIter)]
+pub enum Language {
+    English,
+    German,
+}
+
+/// Which form of a word goes with a number
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Plural {
+    One,
+    Other,
+}
+
+impl Language {
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// Accepts plain codes as well as locale names like `de_DE.UTF-8`
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        Language::iter().find(|language| code.starts_with(language.code()))
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../../assets/locale/en.catalog"),
+            Language::German => include_str!("../../assets/locale/de.catalog"),
+        }
+    }
+
+    fn plural(self, n: i64) -> Plural {
+        match self {
+            Language::English | Language::German => {
+                if n == 1 {
+                    Plural::One
+                } else {
+                    Plural::Other
+                }
+            }
+        }
+    }
+
+    /// `RKTRL_LANG` wins over the system locale, and anything unknown falls back to English
+    #[cfg(not(any(test, target_arch = "wasm32")))]
+    fn detect() -> Self {
+        ["RKTRL_LANG", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find_map(|code| Language::from_code(&code))
+            .unwrap_or(Language::English)
+    }
+
+    #[cfg(any(test, target_arch = "wasm32"))]
+    fn detect() -> Self {
+        Language::English
+    }
+}
+
+/// A run of translated text, highlighted if it came from an argument
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Span {
+    pub text: String,
+    pub highlight: bool,
+}
+
+pub struct Catalog {
+    language: Language,
+    templates: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// # Errors
+    /// On lines that are neither blank, comments nor `key = template`, and on repeated keys
+    pub fn parse(language: Language, source: &str) -> Result<Self, String> {
+        let mut templates = HashMap::new();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap().trim().to_string();
+            let template = parts
+                .next()
+                .ok_or_else(|| format!("line {}: expected `key = template`", i + 1))?;
+            if templates
+                .insert(key.clone(), template.trim().to_string())
+                .is_some()
+            {
+                return Err(format!("line {}: {} is already defined", i + 1, key));
+            }
+        }
+        Ok(Catalog {
+            language,
+            templates,
+        })
+    }
+
+    #[must_use]
+    pub fn load(language: Language) -> Self {
+        Catalog::parse(language, language.source())
+            .unwrap_or_else(|error| panic!("Broken {} catalog, {}", language.code(), error))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn template(&self, key: &str) -> Option<&str> {
+        self.templates.get(key).map(String::as_str)
+    }
+
+    /// The translation of an entity name, see `tr_name`
+    #[must_use]
+    pub fn name(&self, name: &str) -> Option<&str> {
+        self.template(&format!("name.{}", name.to_lowercase().replace(' ', "_")))
+    }
+
+    /// Fills in `template`, keeping the arguments apart from the surrounding text
+    #[must_use]
+    pub fn fill(&self, template: &str, args: &[(&str, String)]) -> Vec<Span> {
+        let mut spans = vec![];
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = start
+                + rest[start..]
+                    .find('}')
+                    .unwrap_or_else(|| panic!("Unclosed placeholder in {}", template));
+            if start > 0 {
+                spans.push(Span {
+                    text: rest[..start].to_string(),
+                    highlight: false,
+                });
+            }
+            spans.push(self.placeholder(&rest[start + 1..end], args));
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            spans.push(Span {
+                text: rest.to_string(),
+                highlight: false,
+            });
+        }
+        spans
+    }
+
+    fn placeholder(&self, placeholder: &str, args: &[(&str, String)]) -> Span {
+        let mut parts = placeholder.split('|');
+        let name = parts.next().unwrap();
+        let value = args
+            .iter()
+            .find(|(arg, _)| *arg == name)
+            .map_or("", |(_, value)| value.as_str());
+        let forms: Vec<&str> = parts.collect();
+        if forms.is_empty() {
+            return Span {
+                text: value.to_string(),
+                highlight: true,
+            };
+        }
+        let n = value.parse().unwrap_or(0);
+        let form = match (self.language.plural(n), forms.as_slice()) {
+            (Plural::One, [one, ..]) => one,
+            (Plural::Other, [_, other, ..]) => other,
+            (_, [only]) => only,
+            _ => unreachable!(),
+        };
+        Span {
+            text: (*form).to_string(),
+            highlight: false,
+        }
+    }
+}
+
+lazy_static! {
+    static ref ENGLISH: Catalog = Catalog::load(Language::English);
+    static ref CURRENT: RwLock<Catalog> = RwLock::new(Catalog::load(Language::detect()));
+}
+
+#[must_use]
+pub fn language() -> Language {
+    CURRENT.read().unwrap().language
+}
+
+pub fn set_language(language: Language) {
+    *CURRENT.write().unwrap() = Catalog::load(language);
+}
+
+/// Text for `key` with its arguments filled in. Keys missing from the chosen language fall back
+/// to English, and keys missing from there show up as themselves.
+#[must_use]
+pub fn tr_spans(key: &str, args: &[(&str, String)]) -> Vec<Span> {
+    let current = CURRENT.read().unwrap();
+    match current.template(key).or_else(|| ENGLISH.template(key)) {
+        Some(template) => current.fill(template, args),
+        None => vec![Span {
+            text: key.to_string(),
+            highlight: false,
+        }],
+    }
+}
+
+#[must_use]
+pub fn tr_args(key: &str, args: &[(&str, String)]) -> String {
+    tr_spans(key, args)
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
+
+#[must_use]
+pub fn tr(key: &str) -> String {
+    tr_args(key, &[])
+}
+
+/// Entity names double as identifiers, so they're kept in English and only translated for
+/// display. Names without a translation are shown as they are.
+#[must_use]
+pub fn tr_name(name: &str) -> String {
+    let current = CURRENT.read().unwrap();
+    current
+        .name(name)
+        .or_else(|| ENGLISH.name(name))
+        .unwrap_or(name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use strum::IntoEnumIterator;
+
+    use crate::util::i18n::*;
+
+    #[test]
+    fn every_catalog_has_every_key() {
+        let english: HashSet<&str> = ENGLISH.keys().collect();
+        for language in Language::iter() {
+            let catalog = Catalog::load(language);
+            let keys: HashSet<&str> = catalog.keys().collect();
+            let mut missing: Vec<_> = english.difference(&keys).collect();
+            let mut extra: Vec<_> = keys.difference(&english).collect();
+            missing.sort();
+            extra.sort();
+            assert!(
+                missing.is_empty() && extra.is_empty(),
+                "{} catalog is missing {:?} and has unknown {:?}",
+                language.code(),
+                missing,
+                extra
+            );
+        }
+    }
+
+    #[test]
+    fn plural_forms_follow_the_number() {
+        let catalog = Catalog::parse(Language::English, "k = {n} {n|turn|turns}").unwrap();
+        let fill = |n: i32| -> String {
+            catalog
+                .fill(catalog.template("k").unwrap(), &[("n", n.to_string())])
+                .into_iter()
+                .map(|span| span.text)
+                .collect()
+        };
+        assert_eq!(fill(1), "1 turn");
+        assert_eq!(fill(3), "3 turns");
+        assert_eq!(fill(0), "0 turns");
+    }
+
+    #[test]
+    fn arguments_are_highlighted() {
+        let catalog = Catalog::parse(Language::English, "k = {a} hits {b}.").unwrap();
+        let spans = catalog.fill(
+            catalog.template("k").unwrap(),
+            &[("a", "Orc".to_string()), ("b", "Goblin".to_string())],
+        );
+        let highlighted: Vec<_> = spans
+            .iter()
+            .filter(|span| span.highlight)
+            .map(|span| span.text.as_str())
+            .collect();
+        assert_eq!(highlighted, vec!["Orc", "Goblin"]);
+    }
+
+    #[test]
+    fn broken_lines_are_rejected() {
+        assert!(Catalog::parse(Language::English, "no separator").is_err());
+        assert!(Catalog::parse(Language::English, "k = a\nk = b").is_err());
+    }
+
+    #[test]
+    fn unknown_names_are_kept() {
+        assert_eq!(Catalog::load(Language::German).name("Potion 7"), None);
+        assert_eq!(tr_name("Potion 7"), "Potion 7");
+    }
+
+    #[test]
+    fn names_are_translated() {
+        let german = Catalog::load(Language::German);
+        assert_eq!(german.name("Orc Warlord"), Some("Ork-Kriegsherr"));
+        assert_eq!(german.name("Health Potion"), Some("Heiltrank"));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod bracket_lib_ext;
+pub mod duplicate;
+pub mod i18n;
 pub mod random_table;
 pub mod rect_ext;
 pub mod saveload;
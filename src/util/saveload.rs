@@ -11,7 +11,7 @@ use std::fs::File;
 use std::io::{Cursor, Error, ErrorKind, Result as IOResult};
 
 use crate::components::SerializeMe;
-use crate::resources::{GameLog, Map};
+use crate::resources::{Clock, GameLog, ItemKnowledge, Map, Statistics};
 
 /// Execute code against each resource type we want to serialize, in a stable order.
 /// Used to guarantee serialization and deserialization use the same order.
@@ -19,6 +19,9 @@ macro_rules! foreach_resource {
     ($obj:ident.$f:ident::<R>($arg:ident)) => {
         $obj.$f::<Map>($arg);
         $obj.$f::<GameLog>($arg);
+        $obj.$f::<ItemKnowledge>($arg);
+        $obj.$f::<Clock>($arg);
+        $obj.$f::<Statistics>($arg);
     };
 }
 
@@ -61,7 +64,8 @@ impl std::io::Write for LocalStorageWriter {
 }
 
 // TODO The `inventory` and `linkme` crates don't support WASM, so we must explicitly list
-//      all components. See https://github.com/rustwasm/wasm-bindgen/issues/1216
+//      all components in `foreach_component`.
+//      See https://github.com/rustwasm/wasm-bindgen/issues/1216
 macro_rules! register_components {
     ($registry:ident, $($component:ident),+) => {
         $(
@@ -75,36 +79,7 @@ lazy_static! {
     static ref REGISTRY: legion::Registry<SerializableTypeUuid> = {
         use crate::components::*;
         let mut registry = legion::Registry::default();
-        register_components!(
-            registry,
-            AreaOfEffect,
-            BlocksTile,
-            CombatStats,
-            Confusion,
-            Consumable,
-            DefenseBonus,
-            EntryTrigger,
-            Equippable,
-            Equipped,
-            Hidden,
-            HungerClock,
-            InBackpack,
-            InflictsDamage,
-            Item,
-            MagicMapper,
-            MeleePowerBonus,
-            Monster,
-            Name,
-            Player,
-            Position,
-            ProvidesFood,
-            ProvidesHealing,
-            Ranged,
-            Renderable,
-            SerializeMe,
-            SingleActivation,
-            Viewshed
-        );
+        foreach_component!(register_components!(registry,));
         registry
     };
 }
@@ -1,6 +1,10 @@
 use legion::{component, storage::Component, world::SubWorld, Entity, EntityStore, IntoQuery};
 
-use crate::components::Player;
+use crate::components::{
+    CombatStats, DefenseBonus, Enchantment, Encumbrance, Equipped, HungerClock, HungerState,
+    InBackpack, MeleePowerBonus, Name, Player, Quantity, Value, Weight,
+};
+use crate::resources::ItemKnowledge;
 
 pub trait WorldExt {
     fn has_component<T: Component>(&self, entity: Entity) -> bool;
@@ -10,6 +14,14 @@ pub trait WorldExt {
     fn maybe_player_entity(&self) -> Option<&Entity>;
     fn player_entity(&self) -> &Entity;
     fn player_component<T: Component + Clone>(&self) -> T;
+
+    fn display_name(&self, entity: Entity, item_knowledge: &ItemKnowledge) -> String;
+    fn carried_weight(&self, owner: Entity) -> f32;
+    fn encumbrance(&self, entity: Entity) -> Encumbrance;
+    fn melee_power(&self, entity: Entity) -> i32;
+    fn defense(&self, entity: Entity) -> i32;
+    fn price(&self, item: Entity) -> i32;
+    fn sell_price(&self, item: Entity) -> i32;
 }
 
 impl<'a> WorldExt for SubWorld<'a> {
@@ -55,4 +67,135 @@ impl<'a> WorldExt for SubWorld<'a> {
     fn player_component<T: Component + Clone>(&self) -> T {
         self.get_component(*self.player_entity())
     }
+
+    /// Name of an entity as the player knows it, respecting identification and enchantments
+    fn display_name(&self, entity: Entity, item_knowledge: &ItemKnowledge) -> String {
+        let entry = self.entry_ref(entity).unwrap();
+        let name = item_knowledge.display_name(entry.get_component::<Name>().unwrap());
+        match entry.get_component::<Enchantment>() {
+            Ok(enchantment) if enchantment.level != 0 => {
+                format!("{:+} {}", enchantment.level, name)
+            }
+            _ => name,
+        }
+    }
+
+    /// Total weight of everything `owner` has in their backpack or equipped
+    fn carried_weight(&self, owner: Entity) -> f32 {
+        <(
+            &Weight,
+            Option<&Quantity>,
+            Option<&InBackpack>,
+            Option<&Equipped>,
+        )>::query()
+        .iter(self)
+        .filter(|(_, _, maybe_in_backpack, maybe_equipped)| {
+            maybe_in_backpack.map(|in_backpack| in_backpack.owner) == Some(owner)
+                || maybe_equipped.map(|equipped| equipped.owner) == Some(owner)
+        })
+        .map(|(weight, maybe_quantity, _, _)| weight.total(maybe_quantity))
+        .sum()
+    }
+
+    /// Entities without `CombatStats` don't carry things around, so they're never encumbered
+    fn encumbrance(&self, entity: Entity) -> Encumbrance {
+        match <(&CombatStats,)>::query().get(self, entity) {
+            Ok((stats,)) => Encumbrance::new(self.carried_weight(entity), stats.carry_capacity()),
+            Err(_) => Encumbrance::Unburdened,
+        }
+    }
+
+    /// Attack power with equipment, being well fed and encumbrance taken into account
+    fn melee_power(&self, entity: Entity) -> i32 {
+        let (stats, maybe_hunger_clock) = <(&CombatStats, Option<&HungerClock>)>::query()
+            .get(self, entity)
+            .unwrap();
+
+        let hunger_bonus = maybe_hunger_clock
+            .map(|clock| match clock.state {
+                HungerState::WellFed => 1,
+                _ => 0,
+            })
+            .unwrap_or(0);
+
+        let equipment_bonus = <(&Equipped, &MeleePowerBonus, Option<&Enchantment>)>::query()
+            .iter(self)
+            .filter(|(equipped, _, _)| equipped.owner == entity)
+            .map(|(_, bonus, enchantment)| bonus.power + Enchantment::level_of(enchantment))
+            .sum::<i32>();
+
+        equipment_bonus + stats.power + hunger_bonus
+            - self.encumbrance(entity).melee_power_penalty()
+    }
+
+    /// Defense with equipment and encumbrance taken into account
+    fn defense(&self, entity: Entity) -> i32 {
+        let (stats,) = <(&CombatStats,)>::query().get(self, entity).unwrap();
+        <(&Equipped, &DefenseBonus, Option<&Enchantment>)>::query()
+            .iter(self)
+            .filter(|(equipped, _, _)| equipped.owner == entity)
+            .map(|(_, bonus, enchantment)| bonus.defense + Enchantment::level_of(enchantment))
+            .sum::<i32>()
+            + stats.defense
+            - self.encumbrance(entity).defense_penalty()
+    }
+
+    /// What a vendor asks for a single `item`. Items without a `Value` are worthless.
+    fn price(&self, item: Entity) -> i32 {
+        match <(&Value, Option<&Enchantment>)>::query().get(self, item) {
+            Ok((value, maybe_enchantment)) => value.price(Enchantment::level_of(maybe_enchantment)),
+            Err(_) => 0,
+        }
+    }
+
+    /// What a vendor pays for a single `item`
+    fn sell_price(&self, item: Entity) -> i32 {
+        match <(&Value, Option<&Enchantment>)>::query().get(self, item) {
+            Ok((value, maybe_enchantment)) => {
+                value.sell_price(Enchantment::level_of(maybe_enchantment))
+            }
+            Err(_) => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::systems::prelude::*;
+    use crate::systems::testing::TestWorld;
+
+    #[test]
+    fn enchantment_shifts_power_and_defense() {
+        let mut test = TestWorld::default();
+        let player = test.player(Position::new(5, 5));
+        let base = test.sub_world(|world| (world.melee_power(player), world.defense(player)));
+
+        let weapon = test.world.push((
+            MeleePowerBonus::new(2),
+            Equipped {
+                owner: player,
+                slot: EquipmentSlot::Melee,
+            },
+        ));
+        let shield = test.world.push((
+            DefenseBonus::new(1),
+            Equipped {
+                owner: player,
+                slot: EquipmentSlot::Shield,
+            },
+        ));
+        let plain = test.sub_world(|world| (world.melee_power(player), world.defense(player)));
+        assert_eq!(plain, (base.0 + 2, base.1 + 1));
+
+        test.world
+            .entry(weapon)
+            .unwrap()
+            .add_component(Enchantment::new(2));
+        test.world
+            .entry(shield)
+            .unwrap()
+            .add_component(Enchantment::new(-2));
+        let enchanted = test.sub_world(|world| (world.melee_power(player), world.defense(player)));
+        assert_eq!(enchanted, (base.0 + 4, base.1 - 1));
+    }
 }